@@ -0,0 +1,286 @@
+//! A blocking client of Toggl Reports API v2.
+//!
+//! This module is enabled by the `blocking` feature. It shares the query types and the report
+//! structures with the async `Toggl`, so no async runtime is needed to fetch reports.
+//!
+//! ```no_run
+//! use toggl_reports::{blocking::Toggl, query::SummaryQuery};
+//!
+//! let toggl = Toggl::new("00000000000000000000000000000000");
+//! let res = toggl.get_summary_report(&SummaryQuery::new(264)).unwrap();
+//! println!("TOTAL: {}s", res.total_grand.num_seconds());
+//! ```
+
+use crate::builder::{DEFAULT_BASE_URL, DEFAULT_UA};
+use crate::error::{self, Result};
+use crate::query::{DetailedQuery, Query, SummaryQuery, WeeklyQuery};
+use crate::transport::BasicAuth;
+use crate::{retry, summary_report, weekly_report};
+use crate::{DetailedReport, RetryPolicy, SummaryReport, WeeklyReport};
+use futures::{executor, future};
+use reqwest::blocking::Client;
+use std::thread;
+
+/// A builder to construct a blocking `Toggl` with custom settings.
+///
+/// ```
+/// use toggl_reports::blocking::Toggl;
+///
+/// let toggl = Toggl::builder("00000000000000000000000000000000")
+///     .user_agent("my-app")
+///     .base_url("http://localhost:8080/reports/api/v2")
+///     .build();
+/// ```
+#[derive(Debug)]
+pub struct TogglBuilder {
+    auth: BasicAuth,
+    user_agent: String,
+    base_url: String,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl TogglBuilder {
+    pub fn new(api_token: &str) -> Self {
+        Self {
+            auth: BasicAuth::api_token(api_token),
+            user_agent: DEFAULT_UA.to_owned(),
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            retry_policy: None,
+        }
+    }
+
+    pub fn user_agent(self, user_agent: &str) -> Self {
+        Self {
+            user_agent: user_agent.to_owned(),
+            ..self
+        }
+    }
+
+    /// Sets the URL which endpoint names (e.g. `summary`) are appended to.
+    pub fn base_url(self, base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            ..self
+        }
+    }
+
+    /// Retries failed requests following `retry_policy`. Requests are not retried by default.
+    pub fn retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy: Some(retry_policy),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Toggl {
+        Toggl {
+            auth: self.auth,
+            user_agent: self.user_agent,
+            base_url: self.base_url,
+            client: Client::new(),
+            retry_policy: self.retry_policy,
+        }
+    }
+}
+
+/// A blocking version of `toggl_reports::Toggl`.
+#[derive(Clone, Debug)]
+pub struct Toggl {
+    auth: BasicAuth,
+    user_agent: String,
+    base_url: String,
+    client: Client,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl Toggl {
+    pub fn new(api_token: &str) -> Self {
+        TogglBuilder::new(api_token).build()
+    }
+
+    pub fn with_user_agent(api_token: &str, user_agent: &str) -> Self {
+        TogglBuilder::new(api_token)
+            .user_agent(user_agent)
+            .build()
+    }
+
+    /// Returns a builder to configure the user agent, the base URL and so on.
+    pub fn builder(api_token: &str) -> TogglBuilder {
+        TogglBuilder::new(api_token)
+    }
+
+    fn rest_get(&self, endpoint: &str, query: &Query) -> Result<String> {
+        let sleep = |delay| {
+            thread::sleep(delay);
+            future::ready(())
+        };
+        executor::block_on(retry::retry(
+            self.retry_policy.as_ref(),
+            || future::ready(self.rest_get_once(endpoint, query)),
+            sleep,
+        ))
+    }
+
+    fn rest_get_once(&self, endpoint: &str, query: &Query) -> Result<String> {
+        let url = format!("{}/{}", self.base_url, endpoint);
+        let mut query_vec = query.to_vec();
+        query_vec.push(("user_agent", self.user_agent.clone()));
+
+        let res = self.client
+            .get(&url)
+            .query(&query_vec)
+            .basic_auth(&self.auth.username, self.auth.password.as_ref())
+            .send()?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes()?;
+        if !status.is_success() {
+            return Err(error::from_response(status, &headers, &body));
+        }
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    pub fn get_weekly_report(&self, query: &WeeklyQuery) -> Result<WeeklyReport> {
+        const ENDPOINT: &str = "weekly";
+
+        query.validate()?;
+        let query = query.as_ref();
+        let res = self.rest_get(ENDPOINT, query)?;
+        error::decode_seed(res, weekly_report::WeeklyReportSeed(query))
+    }
+
+    pub fn get_detailed_report(&self, query: &DetailedQuery) -> Result<DetailedReport> {
+        const ENDPOINT: &str = "details";
+
+        query.validate()?;
+        let query = query.as_ref();
+        let res = self.rest_get(ENDPOINT, query)?;
+        error::decode(res)
+    }
+
+    pub fn get_summary_report(&self, query: &SummaryQuery) -> Result<SummaryReport> {
+        const ENDPOINT: &str = "summary";
+
+        query.validate()?;
+        let query = query.as_ref();
+        let res = self.rest_get(ENDPOINT, query)?;
+        error::decode_seed(res, summary_report::SummaryReportSeed(query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use reqwest::StatusCode;
+    use wiremock::matchers::{basic_auth, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const API_TOKEN: &str = "00000000000000000000000000000000";
+
+    /// The blocking client must be created and dropped outside of async contexts.
+    fn toggl(uri: &str) -> Toggl {
+        Toggl::builder(API_TOKEN)
+            .base_url(uri)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn summary_report_basic() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .and(query_param("workspace_id", "334"))
+            .and(basic_auth(API_TOKEN, "api_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_grand": 334000,
+                "total_billable": null,
+                "total_currencies": [],
+                "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let uri = server.uri();
+        let report = tokio::task::spawn_blocking(move || toggl(&uri).get_summary_report(&SummaryQuery::new(334)))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.total_grand.num_seconds(), 334);
+    }
+
+    #[tokio::test]
+    async fn api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/details"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "error": {"message": "Unauthorized", "tip": null, "code": 403}
+            })))
+            .mount(&server)
+            .await;
+
+        let uri = server.uri();
+        let err = tokio::task::spawn_blocking(move || toggl(&uri).get_detailed_report(&DetailedQuery::new(334)))
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        match err {
+            Error::Api { status, error } => {
+                assert_eq!(status, StatusCode::FORBIDDEN);
+                assert_eq!(error.message, "Unauthorized");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_server_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_grand": 334000,
+                "total_billable": null,
+                "total_currencies": [],
+                "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let uri = server.uri();
+        let report = tokio::task::spawn_blocking(move || {
+            let policy = RetryPolicy::new().base_delay(std::time::Duration::from_millis(1));
+            Toggl::builder(API_TOKEN)
+                .base_url(&uri)
+                .retry_policy(policy)
+                .build()
+                .get_summary_report(&SummaryQuery::new(334))
+        })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.total_grand.num_seconds(), 334);
+    }
+
+    #[test]
+    fn debug_redacts_api_token() {
+        let builder = Toggl::builder(API_TOKEN);
+        assert!(!format!("{:?}", builder).contains(API_TOKEN));
+
+        assert!(!format!("{:?}", builder.build()).contains(API_TOKEN));
+    }
+}
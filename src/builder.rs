@@ -0,0 +1,115 @@
+//! A builder to configure `Toggl`.
+
+use crate::transport::{BasicAuth, ReqwestTransport, Transport};
+use crate::{RateLimiter, RetryPolicy, Toggl};
+use reqwest::Client;
+use std::sync::Arc;
+
+/// The default user agent sent to Toggl
+pub(crate) const DEFAULT_UA: &str = "komori-n<ikamat.kmr@gmail.com>";
+
+/// The default base URL of Toggl Reports API v2
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.track.toggl.com/reports/api/v2";
+
+/// A builder to construct `Toggl` with custom settings.
+///
+/// ```
+/// use toggl_reports::Toggl;
+///
+/// let toggl = Toggl::builder("00000000000000000000000000000000")
+///     .user_agent("my-app")
+///     .base_url("http://localhost:8080/reports/api/v2")
+///     .build();
+/// ```
+#[derive(Debug)]
+pub struct TogglBuilder {
+    auth: BasicAuth,
+    user_agent: String,
+    base_url: String,
+    transport: Option<Arc<dyn Transport>>,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl TogglBuilder {
+    pub fn new(api_token: &str) -> Self {
+        Self {
+            auth: BasicAuth::api_token(api_token),
+            user_agent: DEFAULT_UA.to_owned(),
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            transport: None,
+            rate_limiter: None,
+            retry_policy: None,
+        }
+    }
+
+    pub fn user_agent(self, user_agent: &str) -> Self {
+        Self {
+            user_agent: user_agent.to_owned(),
+            ..self
+        }
+    }
+
+    /// Sets the URL which endpoint names (e.g. `summary`) are appended to.
+    pub fn base_url(self, base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            ..self
+        }
+    }
+
+    /// Sets the `reqwest::Client` used for all requests, e.g. to configure timeouts or proxies.
+    pub fn client(self, client: Client) -> Self {
+        self.transport(ReqwestTransport::new(client))
+    }
+
+    /// Sets the `Transport` used for all requests instead of `reqwest`.
+    pub fn transport<T: Transport + 'static>(self, transport: T) -> Self {
+        Self {
+            transport: Some(Arc::new(transport)),
+            ..self
+        }
+    }
+
+    /// Throttles all requests with `rate_limiter`.
+    ///
+    /// Pass a clone of the same limiter to every `Toggl` that shares an API token.
+    pub fn rate_limiter(self, rate_limiter: RateLimiter) -> Self {
+        Self {
+            rate_limiter: Some(rate_limiter),
+            ..self
+        }
+    }
+
+    /// Retries failed requests following `retry_policy`. Requests are not retried by default.
+    pub fn retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy: Some(retry_policy),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Toggl {
+        Toggl {
+            auth: self.auth,
+            user_agent: self.user_agent,
+            base_url: self.base_url,
+            transport: self.transport.unwrap_or_else(|| Arc::new(ReqwestTransport::default())),
+            rate_limiter: self.rate_limiter,
+            retry_policy: self.retry_policy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_api_token() {
+        let api_token = "00000000000000000000000000000000";
+        let builder = TogglBuilder::new(api_token);
+
+        assert!(!format!("{:?}", builder).contains(api_token));
+    }
+}
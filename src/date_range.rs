@@ -0,0 +1,207 @@
+//! Date ranges to set `since` and `until` of queries.
+//!
+//! ```
+//! use chrono::{FixedOffset, Weekday};
+//! use toggl_reports::date_range::Calendar;
+//! use toggl_reports::query::SummaryQuery;
+//!
+//! let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+//! let calendar = Calendar::new(tokyo).week_start(Weekday::Sun);
+//! let query = SummaryQuery::new(264).date_range(calendar.last_week());
+//! ```
+
+use chrono::{Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+
+/// An inclusive range of dates from `since` to `until`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DateRange {
+    pub since: NaiveDate,
+    pub until: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(since: NaiveDate, until: NaiveDate) -> Self {
+        Self { since, until }
+    }
+
+    /// Returns the week which contains `date`.
+    pub fn week_of(date: NaiveDate, week_start: Weekday) -> Self {
+        let offset =
+            (7 + date.weekday().num_days_from_monday() - week_start.num_days_from_monday()) % 7;
+        let since = date - Duration::days(offset as i64);
+
+        Self::new(since, since + Duration::days(6))
+    }
+
+    /// Returns the month which contains `date`.
+    pub fn month_of(date: NaiveDate) -> Self {
+        let since = date.with_day(1).unwrap();
+        let until = next_month(since).pred_opt().unwrap();
+
+        Self::new(since, until)
+    }
+
+    /// Returns the `quarter`-th quarter of `year`, or `None` unless `quarter` is in 1..=4.
+    pub fn quarter(year: i32, quarter: u32) -> Option<Self> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+
+        let since = NaiveDate::from_ymd_opt(year, 3 * quarter - 2, 1)?;
+        let until = next_month(next_month(next_month(since))).pred_opt()?;
+
+        Some(Self::new(since, until))
+    }
+
+    /// Returns the range from January 1st to `date`.
+    pub fn year_to(date: NaiveDate) -> Self {
+        Self::new(date.with_ordinal(1).unwrap(), date)
+    }
+
+    /// Returns the last `n` days ending at `date`, including `date` itself.
+    ///
+    /// `n` of 0 is treated as 1, i.e. only `date`. The range is clamped at `NaiveDate::MIN`.
+    pub fn last_n_days(date: NaiveDate, n: u32) -> Self {
+        let since = date
+            .checked_sub_signed(Duration::days(n.max(1) as i64 - 1))
+            .unwrap_or(NaiveDate::MIN);
+        Self::new(since, date)
+    }
+}
+
+/// Returns the first day of the next month of `date`.
+fn next_month(date: NaiveDate) -> NaiveDate {
+    match date.month() {
+        12 => NaiveDate::from_ymd_opt(date.year() + 1, 1, 1),
+        month => NaiveDate::from_ymd_opt(date.year(), month + 1, 1),
+    }
+    .unwrap()
+}
+
+/// A calendar to compute date ranges relative to today.
+///
+/// Today is determined in `timezone`, and weeks start on `week_start` (Monday by default).
+#[derive(Clone, Debug)]
+pub struct Calendar<Tz: TimeZone> {
+    timezone: Tz,
+    week_start: Weekday,
+}
+
+impl Default for Calendar<Utc> {
+    fn default() -> Self {
+        Self::new(Utc)
+    }
+}
+
+impl<Tz: TimeZone> Calendar<Tz> {
+    pub fn new(timezone: Tz) -> Self {
+        Self {
+            timezone,
+            week_start: Weekday::Mon,
+        }
+    }
+
+    /// Sets the first day of weeks.
+    pub fn week_start(self, week_start: Weekday) -> Self {
+        Self { week_start, ..self }
+    }
+
+    /// Returns today in the timezone of the calendar.
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    pub fn this_week(&self) -> DateRange {
+        DateRange::week_of(self.today(), self.week_start)
+    }
+
+    pub fn last_week(&self) -> DateRange {
+        DateRange::week_of(self.today() - Duration::days(7), self.week_start)
+    }
+
+    pub fn this_month(&self) -> DateRange {
+        DateRange::month_of(self.today())
+    }
+
+    pub fn last_month(&self) -> DateRange {
+        let first = self.today().with_day(1).unwrap();
+        DateRange::month_of(first.pred_opt().unwrap())
+    }
+
+    pub fn year_to_date(&self) -> DateRange {
+        DateRange::year_to(self.today())
+    }
+
+    /// Returns the last `n` days including today. `n` of 0 is treated as 1.
+    pub fn last_n_days(&self, n: u32) -> DateRange {
+        DateRange::last_n_days(self.today(), n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%F").unwrap()
+    }
+
+    fn range(since: &str, until: &str) -> DateRange {
+        DateRange::new(date(since), date(until))
+    }
+
+    #[test]
+    fn week_of_basic() {
+        // 2021-03-03 is Wednesday
+        assert_eq!(DateRange::week_of(date("2021-03-03"), Weekday::Mon), range("2021-03-01", "2021-03-07"));
+        assert_eq!(DateRange::week_of(date("2021-03-03"), Weekday::Sun), range("2021-02-28", "2021-03-06"));
+        assert_eq!(DateRange::week_of(date("2021-03-01"), Weekday::Mon), range("2021-03-01", "2021-03-07"));
+        assert_eq!(DateRange::week_of(date("2021-02-28"), Weekday::Mon), range("2021-02-22", "2021-02-28"));
+        assert_eq!(DateRange::week_of(date("2021-01-01"), Weekday::Sat), range("2020-12-26", "2021-01-01"));
+    }
+
+    #[test]
+    fn month_of_basic() {
+        assert_eq!(DateRange::month_of(date("2021-02-14")), range("2021-02-01", "2021-02-28"));
+        assert_eq!(DateRange::month_of(date("2020-02-29")), range("2020-02-01", "2020-02-29"));
+        assert_eq!(DateRange::month_of(date("2021-12-31")), range("2021-12-01", "2021-12-31"));
+        assert_eq!(DateRange::month_of(date("2021-04-01")), range("2021-04-01", "2021-04-30"));
+    }
+
+    #[test]
+    fn quarter_basic() {
+        assert_eq!(DateRange::quarter(2020, 1), Some(range("2020-01-01", "2020-03-31")));
+        assert_eq!(DateRange::quarter(2020, 2), Some(range("2020-04-01", "2020-06-30")));
+        assert_eq!(DateRange::quarter(2020, 3), Some(range("2020-07-01", "2020-09-30")));
+        assert_eq!(DateRange::quarter(2020, 4), Some(range("2020-10-01", "2020-12-31")));
+        assert_eq!(DateRange::quarter(2020, 0), None);
+        assert_eq!(DateRange::quarter(2020, 5), None);
+    }
+
+    #[test]
+    fn year_to_and_last_n_days() {
+        assert_eq!(DateRange::year_to(date("2021-03-03")), range("2021-01-01", "2021-03-03"));
+        assert_eq!(DateRange::last_n_days(date("2021-03-03"), 7), range("2021-02-25", "2021-03-03"));
+        assert_eq!(DateRange::last_n_days(date("2021-03-03"), 1), range("2021-03-03", "2021-03-03"));
+        assert_eq!(DateRange::last_n_days(date("2021-03-03"), 0), range("2021-03-03", "2021-03-03"));
+        assert_eq!(DateRange::last_n_days(date("2021-03-03"), u32::MAX), DateRange::new(NaiveDate::MIN, date("2021-03-03")));
+    }
+
+    #[test]
+    fn calendar_relative() {
+        let calendar = Calendar::default().week_start(Weekday::Sun);
+        let today = calendar.today();
+
+        let this_week = calendar.this_week();
+        assert_eq!(this_week.since.weekday(), Weekday::Sun);
+        assert!(this_week.since <= today && today <= this_week.until);
+        assert_eq!(calendar.last_week().until, this_week.since.pred_opt().unwrap());
+
+        let this_month = calendar.this_month();
+        assert_eq!(this_month.since.day(), 1);
+        assert_eq!(calendar.last_month().until, this_month.since.pred_opt().unwrap());
+
+        assert_eq!(calendar.year_to_date().until, today);
+        assert_eq!(calendar.last_n_days(30).since, today - Duration::days(29));
+    }
+}
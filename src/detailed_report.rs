@@ -3,38 +3,176 @@
 //! For more information, see https://github.com/toggl/toggl_api_docs/blob/master/reports/detailed.md.
 
 use chrono::{DateTime, Utc};
-use crate::reports::{EarningWrapper, Currency, DurationWrapper};
-use serde::Deserialize;
+use crate::reports::{self, EarningWrapper, Currency, DurationWrapper};
+use serde::{Deserialize, Serialize};
 
 
 /// A structure to store DetailedReports.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DetailedReport {
-    total_grand: DurationWrapper,
-    total_billable: DurationWrapper,
-    total_count: u64,
-    per_page: u64,
-    total_currencies: Vec<Currency>,
-    data: Vec<DataEntry>,
+    pub total_grand: DurationWrapper,
+    pub total_billable: DurationWrapper,
+    pub total_count: u64,
+    pub per_page: u64,
+    pub total_currencies: Vec<Currency>,
+    pub data: Vec<DataEntry>,
 }
 
-#[derive(Debug, Deserialize)]
+impl DetailedReport {
+    /// Appends entries of `other` and adds up its totals, e.g. to combine reports of different date ranges.
+    pub fn merge(&mut self, other: DetailedReport) {
+        *self.total_grand += *other.total_grand;
+        *self.total_billable += *other.total_billable;
+        self.total_count += other.total_count;
+        reports::merge_currencies(&mut self.total_currencies, other.total_currencies);
+        self.data.extend(other.data);
+    }
+
+    /// Returns the number of pages needed to fetch all `total_count` entries.
+    pub fn page_count(&self) -> u64 {
+        if self.per_page == 0 {
+            return 1;
+        }
+
+        self.total_count.div_ceil(self.per_page)
+    }
+}
+
+/// A structure to store a time entry of DetailedReports.
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DataEntry {
-    id: u64,
-    pid: Option<u64>,
-    tid: Option<u64>,
-    uid: Option<u64>,
-    description: Option<String>,
-    start: DateTime<Utc>,
-    end: DateTime<Utc>,
-    dur: DurationWrapper,
-    user: Option<String>,
-    use_stop: bool,
-    client: Option<String>,
-    project: Option<String>,
-    task: Option<String>,
-    billable: EarningWrapper,
-    is_billable: bool,
-    cur: Option<String>,
-    tags: Vec<String>,
-}
\ No newline at end of file
+    pub id: u64,
+    pub pid: Option<u64>,
+    pub tid: Option<u64>,
+    pub uid: Option<u64>,
+    pub description: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub dur: DurationWrapper,
+    pub user: Option<String>,
+    pub use_stop: bool,
+    pub client: Option<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+    pub billable: EarningWrapper,
+    pub is_billable: bool,
+    pub cur: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_json(total_count: u64, per_page: u64) -> serde_json::Value {
+        serde_json::json!({
+            "total_grand": 0,
+            "total_billable": null,
+            "total_count": total_count,
+            "per_page": per_page,
+            "total_currencies": [],
+            "data": []
+        })
+    }
+
+    fn report(total_count: u64, per_page: u64) -> DetailedReport {
+        serde_json::from_value(report_json(total_count, per_page)).unwrap()
+    }
+
+    #[test]
+    fn page_count_basic() {
+        assert_eq!(report(0, 50).page_count(), 0);
+        assert_eq!(report(1, 50).page_count(), 1);
+        assert_eq!(report(50, 50).page_count(), 1);
+        assert_eq!(report(51, 50).page_count(), 2);
+        assert_eq!(report(334, 50).page_count(), 7);
+    }
+
+    #[test]
+    fn data_entry_basic() {
+        let json = r##"{
+            "id": 334,
+            "pid": 264,
+            "tid": null,
+            "uid": 33,
+            "description": "desc",
+            "start": "2021-02-28T10:00:00+09:00",
+            "end": "2021-02-28T11:30:00+09:00",
+            "dur": 5400000,
+            "updated": "2021-02-28T11:30:00+09:00",
+            "use_stop": true,
+            "client": "cli",
+            "project": "proj",
+            "project_color": "0",
+            "project_hex_color": "#06aaf5",
+            "task": null,
+            "billable": 12.5,
+            "is_billable": true,
+            "cur": "USD",
+            "tags": ["hoge", "fuga"],
+            "user": "us"
+        }"##;
+        let entry: DataEntry = serde_json::from_str(json).unwrap();
+
+        assert_eq!(entry.id, 334);
+        assert_eq!(entry.pid, Some(264));
+        assert_eq!(entry.tid, None);
+        assert_eq!(entry.start.to_rfc3339(), "2021-02-28T01:00:00+00:00");
+        assert_eq!(entry.end - entry.start, *entry.dur);
+        assert_eq!(entry.project.as_deref(), Some("proj"));
+        assert_eq!(*entry.billable, 12.5);
+        assert_eq!(entry.tags, vec!["hoge", "fuga"]);
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let json = serde_json::json!({
+            "total_grand": 5400000,
+            "total_billable": 5400000,
+            "total_count": 1,
+            "per_page": 50,
+            "total_currencies": [{"currency": "USD", "amount": 12.5}],
+            "data": [{
+                "id": 334,
+                "pid": 264,
+                "tid": null,
+                "uid": 33,
+                "description": "desc",
+                "start": "2021-02-28T01:00:00Z",
+                "end": "2021-02-28T02:30:00Z",
+                "dur": 5400000,
+                "user": "us",
+                "use_stop": true,
+                "client": "cli",
+                "project": "proj",
+                "task": null,
+                "billable": 12.5,
+                "is_billable": true,
+                "cur": "USD",
+                "tags": ["hoge"]
+            }]
+        });
+        let report: DetailedReport = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&report).unwrap(), json);
+    }
+
+    #[test]
+    fn merge_basic() {
+        let mut report = report(3, 50);
+        let mut other = report_json(2, 50);
+        other["total_grand"] = serde_json::json!(7200000);
+        other["total_currencies"] = serde_json::json!([{"currency": "USD", "amount": 12.5}]);
+        report.merge(serde_json::from_value(other).unwrap());
+
+        assert_eq!(report.total_count, 5);
+        assert_eq!(report.total_grand.num_hours(), 2);
+        assert_eq!(report.total_currencies.len(), 1);
+        assert_eq!(*report.total_currencies[0].amount, 12.5);
+    }
+
+    #[test]
+    fn page_count_zero_per_page() {
+        assert_eq!(report(334, 0).page_count(), 1);
+    }
+}
@@ -0,0 +1,294 @@
+//! Error types returned by `Toggl`.
+
+use crate::query::ValidationError;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::de::{DeserializeOwned, DeserializeSeed};
+use serde::Deserialize;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// A `Result` alias where the error type is `toggl_reports::Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error returned from calls of Toggl Reports API.
+#[derive(Debug)]
+pub enum Error {
+    /// The query was rejected by `Query::validate()` before sending.
+    Invalid(ValidationError),
+    /// Failed to send a request or to receive its response.
+    Transport(reqwest::Error),
+    /// A custom `Transport` failed without a response. See `Error::custom()`.
+    Custom(Box<dyn std::error::Error + Send + Sync>),
+    /// The API returned a non-success status code with an error message of Toggl.
+    Api {
+        status: StatusCode,
+        error: ApiError,
+    },
+    /// The API returned a non-success status code.
+    Status {
+        status: StatusCode,
+        body: String,
+    },
+    /// The API returned `429 Too Many Requests`.
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    /// Failed to decode a response body.
+    Decode {
+        /// JSON path where decoding failed, e.g. `data[3].title`
+        path: String,
+        /// Raw response body
+        payload: String,
+        source: serde_json::Error,
+    },
+    /// Failed to write a downloaded report file.
+    Io(std::io::Error),
+}
+
+impl Error {
+    /// Wraps a failure of a custom `Transport`, e.g. one which doesn't use reqwest.
+    pub fn custom<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> Self {
+        Error::Custom(err.into())
+    }
+
+    /// Returns the HTTP status code if the error was caused by a response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Invalid(_) => None,
+            Error::Transport(err) => err.status(),
+            Error::Custom(_) => None,
+            Error::Api { status, .. } => Some(*status),
+            Error::Status { status, .. } => Some(*status),
+            Error::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            Error::Decode { .. } => None,
+            Error::Io(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Invalid(err) => write!(f, "{}", err),
+            Error::Transport(err) => write!(f, "transport error: {}", err),
+            Error::Custom(err) => write!(f, "transport error: {}", err),
+            Error::Api { status, error } => write!(f, "api error {}: {}", status, error),
+            Error::Status { status, body } => write!(f, "unexpected status {}: {}", status, body),
+            Error::RateLimited { retry_after: Some(retry_after) } => {
+                write!(f, "rate limited, retry after {}s", retry_after.as_secs())
+            }
+            Error::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            Error::Decode { path, source, .. } => write!(f, "failed to decode response at {}: {}", path, source),
+            Error::Io(err) => write!(f, "io error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Invalid(err) => Some(err),
+            Error::Transport(err) => Some(err),
+            Error::Custom(err) => Some(err.as_ref()),
+            Error::Decode { source, .. } => Some(source),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// An error message returned by Toggl with non-success status codes.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ApiError {
+    pub message: String,
+    pub tip: Option<String>,
+    pub code: Option<u16>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(tip) = &self.tip {
+            write!(f, " ({})", tip)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A wrapper of `ApiError` in response bodies, i.e. `{"error": {...}}`
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    error: ApiError,
+}
+
+impl From<ValidationError> for Error {
+    fn from(err: ValidationError) -> Self {
+        Error::Invalid(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Transport(err)
+    }
+}
+
+/// Builds `Error::RateLimited` from headers of a 429 response.
+pub(crate) fn rate_limited(headers: &HeaderMap) -> Error {
+    let retry_after = headers
+        .get(RETRY_AFTER)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse().ok())
+        .map(Duration::from_secs);
+
+    Error::RateLimited { retry_after }
+}
+
+/// Builds an error from a non-success response.
+///
+/// Returns `Error::Api` if `body` is an error message of Toggl, or `Error::Status` otherwise.
+pub(crate) fn from_status(status: StatusCode, body: String) -> Error {
+    match serde_json::from_str::<ApiErrorBody>(&body) {
+        Ok(ApiErrorBody { error }) => Error::Api { status, error },
+        Err(_) => Error::Status { status, body },
+    }
+}
+
+/// Builds an error from a response with a non-success `status`.
+pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Error {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return rate_limited(headers);
+    }
+
+    from_status(status, String::from_utf8_lossy(body).into_owned())
+}
+
+/// Deserializes `payload`, keeping the JSON path of the failure in the error.
+pub(crate) fn decode<T: DeserializeOwned>(payload: String) -> Result<T> {
+    decode_seed(payload, PhantomData)
+}
+
+/// Deserializes `payload` with `seed`, keeping the JSON path of the failure in the error.
+pub(crate) fn decode_seed<S, T>(payload: String, seed: S) -> Result<T>
+    where S: for<'de> DeserializeSeed<'de, Value = T>
+{
+    let mut track = serde_path_to_error::Track::new();
+    let result = {
+        let mut deserializer = serde_json::Deserializer::from_str(&payload);
+        seed.deserialize(serde_path_to_error::Deserializer::new(&mut deserializer, &mut track))
+            .and_then(|value| deserializer.end().map(|_| value))
+    };
+
+    result.map_err(|source| Error::Decode {
+        path: track.path().to_string(),
+        source,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Hoge {
+        #[allow(dead_code)]
+        items: Vec<Fuga>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Fuga {
+        #[allow(dead_code)]
+        id: u64,
+    }
+
+    #[test]
+    fn decode_basic() {
+        let result: Result<Hoge> = decode(String::from(r#"{"items": [{"id": 334}]}"#));
+
+        assert_eq!(result.unwrap().items.len(), 1);
+    }
+
+    #[test]
+    fn decode_path() {
+        let json = String::from(r#"{"items": [{"id": 334}, {"id": "264"}]}"#);
+        let result: Result<Hoge> = decode(json.clone());
+
+        match result {
+            Err(Error::Decode { path, payload, .. }) => {
+                assert_eq!(path, "items[1].id");
+                assert_eq!(payload, json);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_trailing() {
+        let result: Result<Hoge> = decode(String::from(r#"{"items": []} {}"#));
+
+        assert!(matches!(result, Err(Error::Decode { .. })));
+    }
+
+    #[test]
+    fn from_status_api() {
+        let body = r#"{"error": {"message": "We are sorry, this Workspace is not found", "tip": "Check the workspace id", "code": 404}}"#;
+        let err = from_status(StatusCode::NOT_FOUND, String::from(body));
+
+        match err {
+            Error::Api { status, error } => {
+                assert_eq!(status, StatusCode::NOT_FOUND);
+                assert_eq!(error, ApiError {
+                    message: String::from("We are sorry, this Workspace is not found"),
+                    tip: Some(String::from("Check the workspace id")),
+                    code: Some(404),
+                });
+                assert_eq!(error.to_string(), "We are sorry, this Workspace is not found (Check the workspace id)");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn from_status_other() {
+        let body = String::from("<html>Bad Gateway</html>");
+        let err = from_status(StatusCode::BAD_GATEWAY, body.clone());
+
+        match err {
+            Error::Status { status, body: result } => {
+                assert_eq!(status, StatusCode::BAD_GATEWAY);
+                assert_eq!(result, body);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn status_code() {
+        let status = Error::Status { status: StatusCode::FORBIDDEN, body: String::new() };
+        let rate_limited = Error::RateLimited { retry_after: Some(Duration::from_secs(3)) };
+
+        assert_eq!(status.status(), Some(StatusCode::FORBIDDEN));
+        assert_eq!(rate_limited.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(rate_limited.to_string(), "rate limited, retry after 3s");
+    }
+
+    #[test]
+    fn custom_error() {
+        let err = Error::custom(std::io::Error::other("connection reset"));
+
+        assert_eq!(err.status(), None);
+        assert_eq!(err.to_string(), "transport error: connection reset");
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
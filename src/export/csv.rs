@@ -0,0 +1,333 @@
+//! CSV export of detailed reports.
+//!
+//! ```
+//! use toggl_reports::export::csv::{Column, CsvExporter};
+//! use toggl_reports::export::DurationFormat;
+//!
+//! let exporter = CsvExporter::new()
+//!     .columns(vec![Column::User, Column::Project, Column::Start, Column::Duration])
+//!     .delimiter(';')
+//!     .duration_format(DurationFormat::DecimalHours);
+//!
+//! let mut buf = Vec::new();
+//! exporter.write_entries(&mut buf, &[]).unwrap();
+//! assert_eq!(buf, b"User;Project;Start;Duration\n");
+//! ```
+
+use super::DurationFormat;
+use crate::detailed_report::{DataEntry, DetailedReport};
+use crate::weekly_report::{PivotValue, WeeklyPivot};
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// Columns which can be written for each time entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Column {
+    User,
+    Client,
+    Project,
+    Task,
+    Description,
+    Start,
+    End,
+    Duration,
+    BillableAmount,
+    Currency,
+    Tags,
+}
+
+impl Column {
+    /// All columns in the default order.
+    pub const ALL: [Column; 11] = [
+        Column::User,
+        Column::Client,
+        Column::Project,
+        Column::Task,
+        Column::Description,
+        Column::Start,
+        Column::End,
+        Column::Duration,
+        Column::BillableAmount,
+        Column::Currency,
+        Column::Tags,
+    ];
+
+    /// Returns the name written in the header row.
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::User => "User",
+            Column::Client => "Client",
+            Column::Project => "Project",
+            Column::Task => "Task",
+            Column::Description => "Description",
+            Column::Start => "Start",
+            Column::End => "End",
+            Column::Duration => "Duration",
+            Column::BillableAmount => "Billable amount",
+            Column::Currency => "Currency",
+            Column::Tags => "Tags",
+        }
+    }
+}
+
+/// A writer of time entries as CSV rows.
+///
+/// By default, all columns are written with a header row, separated by `,`.
+/// Start and end are written in RFC 3339, and tags are joined with `, `.
+#[derive(Clone, Debug)]
+pub struct CsvExporter {
+    columns: Vec<Column>,
+    delimiter: char,
+    duration_format: DurationFormat,
+    header: bool,
+}
+
+impl Default for CsvExporter {
+    fn default() -> Self {
+        Self {
+            columns: Column::ALL.to_vec(),
+            delimiter: ',',
+            duration_format: DurationFormat::default(),
+            header: true,
+        }
+    }
+}
+
+impl CsvExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets columns to write, in the order of `columns`.
+    pub fn columns(self, columns: Vec<Column>) -> Self {
+        Self { columns, ..self }
+    }
+
+    pub fn delimiter(self, delimiter: char) -> Self {
+        Self { delimiter, ..self }
+    }
+
+    pub fn duration_format(self, duration_format: DurationFormat) -> Self {
+        Self {
+            duration_format,
+            ..self
+        }
+    }
+
+    /// Sets whether `write_entries()` and `write_report()` write the header row.
+    pub fn header(self, header: bool) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Writes the header row.
+    pub fn write_header<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let fields: Vec<Cow<'_, str>> = self.columns.iter().map(|column| column.header().into()).collect();
+        self.write_row(&mut writer, &fields)
+    }
+
+    /// Writes a row of `entry`, e.g. for entries from `Toggl::detailed_entries()`.
+    pub fn write_entry<W: Write>(&self, mut writer: W, entry: &DataEntry) -> io::Result<()> {
+        let fields: Vec<Cow<'_, str>> = self.columns.iter().map(|column| self.field(entry, column)).collect();
+        self.write_row(&mut writer, &fields)
+    }
+
+    /// Writes the header row, if enabled, and rows of `entries`.
+    pub fn write_entries<'a, W, I>(&self, mut writer: W, entries: I) -> io::Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = &'a DataEntry>,
+    {
+        if self.header {
+            self.write_header(&mut writer)?;
+        }
+        for entry in entries {
+            self.write_entry(&mut writer, entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes all entries of `report`. See `write_entries()`.
+    pub fn write_report<W: Write>(&self, writer: W, report: &DetailedReport) -> io::Result<()> {
+        self.write_entries(writer, &report.data)
+    }
+
+    /// Writes a row for each project or user of `pivot`, with a column for each date and the week total.
+    ///
+    /// Columns set by `columns()` are ignored. Pivots of earnings get a `Currency` column,
+    /// and their amounts are written without currencies.
+    pub fn write_weekly_pivot<W: Write>(&self, mut writer: W, pivot: &WeeklyPivot) -> io::Result<()> {
+        let earnings = pivot.rows.iter().any(|row| matches!(row.total, PivotValue::Earning { .. }));
+
+        if self.header {
+            let mut fields: Vec<Cow<'_, str>> = vec!["Title".into()];
+            if earnings {
+                fields.push("Currency".into());
+            }
+            fields.extend(pivot.dates.iter().map(|date| date.format("%F").to_string().into()));
+            fields.push("Total".into());
+            self.write_row(&mut writer, &fields)?;
+        }
+
+        for row in &pivot.rows {
+            let mut fields: Vec<Cow<'_, str>> = vec![row.title.as_str().into()];
+            if earnings {
+                let currency = match &row.total {
+                    PivotValue::Earning { currency, .. } => currency.as_deref().unwrap_or_default(),
+                    PivotValue::Time(_) => "",
+                };
+                fields.push(currency.into());
+            }
+            fields.extend(row.days.iter().chain(Some(&row.total)).map(|value| match value {
+                PivotValue::Time(time) => self.duration_format.format(time).into(),
+                PivotValue::Earning { amount, .. } => format!("{:.2}", amount).into(),
+            }));
+            self.write_row(&mut writer, &fields)?;
+        }
+
+        Ok(())
+    }
+
+    fn field<'a>(&self, entry: &'a DataEntry, column: &Column) -> Cow<'a, str> {
+        let text = |text: &'a Option<String>| text.as_deref().unwrap_or_default().into();
+        match column {
+            Column::User => text(&entry.user),
+            Column::Client => text(&entry.client),
+            Column::Project => text(&entry.project),
+            Column::Task => text(&entry.task),
+            Column::Description => text(&entry.description),
+            Column::Start => entry.start.to_rfc3339().into(),
+            Column::End => entry.end.to_rfc3339().into(),
+            Column::Duration => self.duration_format.format(&entry.dur).into(),
+            Column::BillableAmount => format!("{:.2}", *entry.billable).into(),
+            Column::Currency => text(&entry.cur),
+            Column::Tags => entry.tags.join(", ").into(),
+        }
+    }
+
+    fn write_row<W: Write>(&self, writer: &mut W, fields: &[Cow<'_, str>]) -> io::Result<()> {
+        let mut delimiter = [0; 4];
+        let delimiter = self.delimiter.encode_utf8(&mut delimiter);
+
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(delimiter.as_bytes())?;
+            }
+            if field.contains([self.delimiter, '"', '\n', '\r']) {
+                write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+            } else {
+                writer.write_all(field.as_bytes())?;
+            }
+        }
+        writer.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> DetailedReport {
+        serde_json::from_value(serde_json::json!({
+            "total_grand": 5400000,
+            "total_billable": 5400000,
+            "total_count": 2,
+            "per_page": 50,
+            "total_currencies": [{"currency": "USD", "amount": 12.5}],
+            "data": [{
+                "id": 334,
+                "pid": 264,
+                "tid": null,
+                "uid": 33,
+                "description": "review, \"urgent\"",
+                "start": "2021-02-28T10:00:00+09:00",
+                "end": "2021-02-28T11:30:00+09:00",
+                "dur": 5400000,
+                "use_stop": true,
+                "client": "cli",
+                "project": "proj",
+                "task": null,
+                "billable": 12.5,
+                "is_billable": true,
+                "cur": "USD",
+                "tags": ["hoge", "fuga"],
+                "user": "us"
+            }, {
+                "id": 335,
+                "pid": null,
+                "tid": null,
+                "uid": 33,
+                "description": null,
+                "start": "2021-02-28T12:00:00+09:00",
+                "end": "2021-02-28T12:00:30+09:00",
+                "dur": 30000,
+                "use_stop": true,
+                "client": null,
+                "project": null,
+                "task": null,
+                "billable": null,
+                "is_billable": false,
+                "cur": null,
+                "tags": [],
+                "user": "us"
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn write_report_default() {
+        let mut buf = Vec::new();
+        CsvExporter::new().write_report(&mut buf, &report()).unwrap();
+
+        let ans = "\
+User,Client,Project,Task,Description,Start,End,Duration,Billable amount,Currency,Tags
+us,cli,proj,,\"review, \"\"urgent\"\"\",2021-02-28T01:00:00+00:00,2021-02-28T02:30:00+00:00,1:30:00,12.50,USD,\"hoge, fuga\"
+us,,,,,2021-02-28T03:00:00+00:00,2021-02-28T03:00:30+00:00,0:00:30,0.00,,
+";
+        assert_eq!(String::from_utf8(buf).unwrap(), ans);
+    }
+
+    #[test]
+    fn write_report_custom() {
+        let exporter = CsvExporter::new()
+            .columns(vec![Column::Tags, Column::Duration, Column::Description])
+            .delimiter(';')
+            .duration_format(DurationFormat::DecimalHours)
+            .header(false);
+        let mut buf = Vec::new();
+        exporter.write_report(&mut buf, &report()).unwrap();
+
+        let ans = "\
+hoge, fuga;1.50;\"review, \"\"urgent\"\"\"
+;0.01;
+";
+        assert_eq!(String::from_utf8(buf).unwrap(), ans);
+    }
+
+    #[test]
+    fn write_weekly_pivot_earnings() {
+        use crate::query::WeeklyQuery;
+        use crate::weekly_report::WeeklyReport;
+        use chrono::NaiveDate;
+
+        let json = include_str!("../../tests/fixtures/weekly/users_earnings.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let query = WeeklyQuery::new(334).since_date(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap());
+        let mut buf = Vec::new();
+        CsvExporter::new()
+            .delimiter(';')
+            .write_weekly_pivot(&mut buf, &report.pivot(&query))
+            .unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        let mut lines = out.lines();
+        assert_eq!(
+            lines.next(),
+            Some("Title;Currency;2021-03-01;2021-03-02;2021-03-03;2021-03-04;2021-03-05;2021-03-06;2021-03-07;Total")
+        );
+        assert_eq!(lines.next(), Some("John Swift;EUR;0.00;10.00;0.00;5.00;0.00;0.00;0.00;15.00"));
+        assert_eq!(lines.next(), Some("Jane Doe;EUR;0.00;20.00;0.00;5.00;0.00;0.00;0.00;25.00"));
+        assert_eq!(lines.next(), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! HTML pages of reports.
+//!
+//! `HtmlRenderer` renders a self-contained page with inline styles. Its contexts can also be
+//! rendered with custom templates: enable the `templates` feature to use `Template`, or pass
+//! them to any template engine, since they implement `Serialize`.
+//!
+//! ```
+//! use toggl_reports::export::html::HtmlRenderer;
+//!
+//! let renderer = HtmlRenderer::new().title("Weekly hours of ACME");
+//! # let report: toggl_reports::SummaryReport = serde_json::from_str(
+//! #     r#"{"total_grand": 0, "total_billable": null, "total_currencies": [], "data": []}"#).unwrap();
+//! let html = renderer.render_summary(&report);
+//! ```
+
+use super::table::{self, Align, RowKind, Table};
+use super::DurationFormat;
+use crate::detailed_report::DetailedReport;
+use crate::query::WeeklyQuery;
+use crate::summary_report::SummaryReport;
+use crate::weekly_report::WeeklyReport;
+use chrono::{TimeZone, Utc};
+use serde::Serialize;
+use std::fmt::Write;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; color: #222; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: left; }
+th { border-bottom: 2px solid #888; }
+.numeric { text-align: right; font-variant-numeric: tabular-nums; }
+tr.group, tr.total { font-weight: bold; }
+tr.item td:first-child { padding-left: 2em; }
+tr.total td { border-top: 2px solid #888; }
+";
+
+/// Values passed to templates to render a report.
+///
+/// All cells are already formatted as strings, e.g. titles by `Title::name()`.
+#[derive(Clone, Debug, Serialize)]
+pub struct HtmlContext {
+    pub title: String,
+    pub columns: Vec<HtmlColumn>,
+    pub rows: Vec<HtmlRow>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HtmlColumn {
+    pub name: String,
+    /// Whether the column holds numbers, which are usually aligned right
+    pub numeric: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HtmlRow {
+    /// Serialized as `group`, `item`, `entry` or `total`
+    pub kind: RowKind,
+    pub cells: Vec<String>,
+}
+
+impl HtmlContext {
+    fn new(title: String, table: Table) -> Self {
+        let columns = table.header
+            .into_iter()
+            .zip(table.align)
+            .map(|(name, align)| HtmlColumn {
+                name,
+                numeric: align == Align::Right,
+            })
+            .collect();
+        let rows = table.rows
+            .into_iter()
+            .map(|(kind, cells)| HtmlRow { kind, cells })
+            .collect();
+
+        Self { title, columns, rows }
+    }
+
+    /// Renders the context with the default template.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let title = escape(&self.title);
+
+        writeln!(out, "<!DOCTYPE html>").unwrap();
+        writeln!(out, "<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+        writeln!(out, "<title>{}</title>\n<style>\n{}</style>", title, STYLE).unwrap();
+        writeln!(out, "</head>\n<body>\n<h1>{}</h1>\n<table>\n<thead>\n<tr>", title).unwrap();
+        for column in &self.columns {
+            writeln!(out, "<th{}>{}</th>", class(column.numeric), escape(&column.name)).unwrap();
+        }
+        writeln!(out, "</tr>\n</thead>\n<tbody>").unwrap();
+        for row in &self.rows {
+            writeln!(out, "<tr class=\"{}\">", row_class(row.kind)).unwrap();
+            for (column, cell) in self.columns.iter().zip(&row.cells) {
+                writeln!(out, "<td{}>{}</td>", class(column.numeric), escape(cell)).unwrap();
+            }
+            writeln!(out, "</tr>").unwrap();
+        }
+        writeln!(out, "</tbody>\n</table>\n</body>\n</html>").unwrap();
+
+        out
+    }
+}
+
+fn class(numeric: bool) -> &'static str {
+    if numeric {
+        " class=\"numeric\""
+    } else {
+        ""
+    }
+}
+
+fn row_class(kind: RowKind) -> &'static str {
+    match kind {
+        RowKind::Group => "group",
+        RowKind::Item => "item",
+        RowKind::Entry => "entry",
+        RowKind::Total => "total",
+    }
+}
+
+/// Escapes `text` to be put in HTML elements and attributes.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// A renderer of reports as HTML pages.
+///
+/// Durations are written as `h:mm` by default. Time entries are dated in `timezone`, UTC by default.
+#[derive(Clone, Debug)]
+pub struct HtmlRenderer<Tz: TimeZone = Utc> {
+    title: Option<String>,
+    duration_format: DurationFormat,
+    timezone: Tz,
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        Self {
+            title: None,
+            duration_format: DurationFormat::HoursMinutes,
+            timezone: Utc,
+        }
+    }
+}
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Tz: TimeZone> HtmlRenderer<Tz> {
+    /// Sets the timezone of the workspace, in which dates of time entries are written.
+    pub fn timezone<Tz2: TimeZone>(self, timezone: Tz2) -> HtmlRenderer<Tz2> {
+        HtmlRenderer {
+            title: self.title,
+            duration_format: self.duration_format,
+            timezone,
+        }
+    }
+
+    /// Sets the title of pages. The kind of the report, e.g. `Summary report`, is used by default.
+    pub fn title<S: Into<String>>(self, title: S) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    pub fn duration_format(self, duration_format: DurationFormat) -> Self {
+        Self {
+            duration_format,
+            ..self
+        }
+    }
+
+    pub fn summary_context(&self, report: &SummaryReport) -> HtmlContext {
+        self.context("Summary report", table::summary_table(report, self.duration_format))
+    }
+
+    /// Returns the context of `report` with columns labelled by dates of the week of `query`.
+    ///
+    /// See `WeeklyReport::pivot()` for the dates when `since` of `query` is not set.
+    pub fn weekly_context(&self, report: &WeeklyReport, query: &WeeklyQuery) -> HtmlContext {
+        let dates = report.pivot(query).dates;
+        self.context("Weekly report", table::weekly_table(report, &dates, self.duration_format))
+    }
+
+    pub fn detailed_context(&self, report: &DetailedReport) -> HtmlContext {
+        self.context("Detailed report", table::detailed_table(report, &self.timezone, self.duration_format))
+    }
+
+    /// Renders groups and items of `report` with the default template.
+    pub fn render_summary(&self, report: &SummaryReport) -> String {
+        self.summary_context(report).render()
+    }
+
+    /// Renders groups and details of `report` by dates of the week of `query` with the default template.
+    pub fn render_weekly(&self, report: &WeeklyReport, query: &WeeklyQuery) -> String {
+        self.weekly_context(report, query).render()
+    }
+
+    /// Renders time entries of `report` with the default template.
+    pub fn render_detailed(&self, report: &DetailedReport) -> String {
+        self.detailed_context(report).render()
+    }
+
+    fn context(&self, default_title: &str, table: Table) -> HtmlContext {
+        let title = self.title.clone().unwrap_or_else(|| default_title.to_owned());
+        HtmlContext::new(title, table)
+    }
+}
+
+/// A custom template written in the syntax of [MiniJinja](https://docs.rs/minijinja).
+///
+/// Templates are rendered with fields of `HtmlContext`, and values are HTML-escaped automatically.
+///
+/// ```
+/// use toggl_reports::export::html::{HtmlRenderer, Template};
+///
+/// let template = Template::new("<ul>{% for row in rows %}<li>{{ row.cells[0] }}</li>{% endfor %}</ul>").unwrap();
+/// # let report: toggl_reports::SummaryReport = serde_json::from_str(
+/// #     r#"{"total_grand": 0, "total_billable": null, "total_currencies": [], "data": []}"#).unwrap();
+/// let html = template.render(&HtmlRenderer::new().summary_context(&report)).unwrap();
+/// assert_eq!(html, "<ul><li>Total</li></ul>");
+/// ```
+#[cfg(feature = "templates")]
+#[derive(Clone, Debug)]
+pub struct Template {
+    source: String,
+}
+
+#[cfg(feature = "templates")]
+impl Template {
+    /// The name of templates, whose extension enables HTML escaping
+    const NAME: &'static str = "report.html";
+
+    /// Parses `source`, returning syntax errors if any.
+    pub fn new<S: Into<String>>(source: S) -> Result<Self, minijinja::Error> {
+        let source = source.into();
+        minijinja::Environment::new().template_from_named_str(Self::NAME, &source)?;
+
+        Ok(Self { source })
+    }
+
+    pub fn render(&self, context: &HtmlContext) -> Result<String, minijinja::Error> {
+        minijinja::Environment::new().render_named_str(Self::NAME, &self.source, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn summary() -> SummaryReport {
+        let json = include_str!("../../tests/fixtures/summary/projects_time_entries.json");
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn escape_basic() {
+        assert_eq!(escape("<a href='x'>\"Tom & Jerry\"</a>"), "&lt;a href=&#39;x&#39;&gt;&quot;Tom &amp; Jerry&quot;&lt;/a&gt;");
+    }
+
+    #[test]
+    fn render_summary_default() {
+        let html = HtmlRenderer::new().title("<ACME>").render_summary(&summary());
+
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains("<title>&lt;ACME&gt;</title>"));
+        assert!(html.contains("<tr class=\"group\">\n<td>Toggl Development</td>\n<td class=\"numeric\">3:00</td>\n</tr>"));
+        assert!(html.contains("<tr class=\"item\">\n<td>Hard work</td>\n<td class=\"numeric\">1:00</td>\n</tr>"));
+        assert!(html.contains("<tr class=\"total\">\n<td>Total</td>\n<td class=\"numeric\">6:00</td>\n</tr>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn weekly_context_basic() {
+        let json = include_str!("../../tests/fixtures/weekly/projects_time.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let query = WeeklyQuery::new(334).since_date(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap());
+        let context = HtmlRenderer::new().weekly_context(&report, &query);
+
+        assert_eq!(context.title, "Weekly report");
+        assert_eq!(context.columns.len(), 9);
+        assert_eq!(context.columns[1].name, "2021-03-01");
+        assert_eq!(context.columns[7].name, "2021-03-07");
+        assert_eq!(context.rows[0].cells[0], "Toggl Development");
+        assert_eq!(context.rows[0].cells[8], "1:30");
+        let total = context.rows.last().unwrap();
+        assert_eq!(total.kind, RowKind::Total);
+        assert_eq!(total.cells[1..], ["0:00", "3:00", "0:00", "1:00", "0:00", "0:00", "0:00", "4:00"]);
+    }
+
+    #[test]
+    fn weekly_context_earnings() {
+        let json = include_str!("../../tests/fixtures/weekly/users_earnings.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let query = WeeklyQuery::new(334).since_date(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap());
+        let context = HtmlRenderer::new().weekly_context(&report, &query);
+        let total = context.rows.last().unwrap();
+
+        assert_eq!(total.cells[1..], ["0.00 EUR", "30.00 EUR", "0.00 EUR", "10.00 EUR", "0.00 EUR", "0.00 EUR", "0.00 EUR", "40.00 EUR"]);
+    }
+
+    #[test]
+    fn detailed_context_basic() {
+        let json = serde_json::json!({
+            "total_grand": 5400000,
+            "total_billable": 5400000,
+            "total_count": 1,
+            "per_page": 50,
+            "total_currencies": [{"currency": "USD", "amount": 12.5}],
+            "data": [{
+                "id": 334,
+                "pid": 264,
+                "tid": null,
+                "uid": 33,
+                "description": "desc",
+                "start": "2021-02-28T10:00:00+09:00",
+                "end": "2021-02-28T11:30:00+09:00",
+                "dur": 5400000,
+                "use_stop": true,
+                "client": "cli",
+                "project": "proj",
+                "task": null,
+                "billable": 12.5,
+                "is_billable": true,
+                "cur": "USD",
+                "tags": [],
+                "user": "us"
+            }]
+        });
+        let report: DetailedReport = serde_json::from_value(json).unwrap();
+        let context = HtmlRenderer::new().detailed_context(&report);
+
+        assert_eq!(context.rows[0].kind, RowKind::Entry);
+        assert_eq!(context.rows[0].cells, ["2021-02-28", "desc", "proj", "us", "1:30", "12.50 USD"]);
+        assert_eq!(context.rows[1].cells, ["Total", "", "", "", "1:30", "12.50 USD"]);
+    }
+
+    #[test]
+    fn detailed_context_timezone() {
+        let json = serde_json::json!({
+            "total_grand": 3600000,
+            "total_billable": null,
+            "total_count": 1,
+            "per_page": 50,
+            "total_currencies": [],
+            "data": [{
+                "id": 334,
+                "pid": null,
+                "tid": null,
+                "uid": 33,
+                "description": "morning",
+                "start": "2021-03-01T08:00:00+09:00",
+                "end": "2021-03-01T09:00:00+09:00",
+                "dur": 3600000,
+                "use_stop": true,
+                "client": null,
+                "project": null,
+                "task": null,
+                "billable": null,
+                "is_billable": false,
+                "cur": null,
+                "tags": [],
+                "user": "us"
+            }]
+        });
+        let report: DetailedReport = serde_json::from_value(json).unwrap();
+        let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+
+        assert_eq!(HtmlRenderer::new().detailed_context(&report).rows[0].cells[0], "2021-02-28");
+        assert_eq!(HtmlRenderer::new().timezone(tokyo).detailed_context(&report).rows[0].cells[0], "2021-03-01");
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn custom_template() {
+        let template = Template::new(
+            "<h1>{{ title }}</h1>{% for row in rows if row.kind == \"group\" %}<p>{{ row.cells | join(\", \") }}</p>{% endfor %}"
+        ).unwrap();
+        let context = HtmlRenderer::new().title("A & B").summary_context(&summary());
+
+        assert_eq!(
+            template.render(&context).unwrap(),
+            "<h1>A &amp; B</h1><p>Toggl Development, 3:00</p><p>(none), 3:00</p>"
+        );
+        assert!(Template::new("{% for %}").is_err());
+    }
+}
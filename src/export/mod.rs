@@ -0,0 +1,51 @@
+//! Local exports of reports to other formats.
+
+pub mod csv;
+pub mod html;
+pub mod table;
+
+use chrono::Duration;
+
+/// Formats to write durations in exports.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DurationFormat {
+    /// `h:mm:ss`, e.g. `1:30:00`
+    #[default]
+    HoursMinutesSeconds,
+    /// `h:mm`, e.g. `1:30`. Seconds are truncated.
+    HoursMinutes,
+    /// Hours with two decimal places, e.g. `1.50`
+    DecimalHours,
+    /// Whole seconds, e.g. `5400`
+    Seconds,
+}
+
+impl DurationFormat {
+    pub fn format(&self, duration: &Duration) -> String {
+        let seconds = duration.num_seconds();
+        match self {
+            DurationFormat::HoursMinutesSeconds => {
+                format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+            }
+            DurationFormat::HoursMinutes => format!("{}:{:02}", seconds / 3600, seconds % 3600 / 60),
+            DurationFormat::DecimalHours => format!("{:.2}", duration.num_milliseconds() as f64 / 3_600_000.0),
+            DurationFormat::Seconds => seconds.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_format_basic() {
+        let duration = Duration::seconds(5 * 3600 + 7 * 60 + 9);
+
+        assert_eq!(DurationFormat::HoursMinutesSeconds.format(&duration), "5:07:09");
+        assert_eq!(DurationFormat::HoursMinutes.format(&duration), "5:07");
+        assert_eq!(DurationFormat::DecimalHours.format(&duration), "5.12");
+        assert_eq!(DurationFormat::Seconds.format(&duration), "18429");
+        assert_eq!(DurationFormat::HoursMinutes.format(&Duration::hours(123)), "123:00");
+    }
+}
@@ -0,0 +1,449 @@
+//! Markdown and plain-text tables of reports.
+//!
+//! ```
+//! use toggl_reports::export::table::{TableRenderer, TableStyle};
+//! use toggl_reports::export::DurationFormat;
+//!
+//! let renderer = TableRenderer::new()
+//!     .style(TableStyle::Markdown)
+//!     .duration_format(DurationFormat::DecimalHours);
+//! # let report: toggl_reports::SummaryReport = serde_json::from_str(
+//! #     r#"{"total_grand": 0, "total_billable": null, "total_currencies": [], "data": []}"#).unwrap();
+//! println!("{}", renderer.render_summary(&report));
+//! ```
+
+use super::DurationFormat;
+use crate::detailed_report::DetailedReport;
+use crate::reports::{Currency, EarningTotals, TimeTotals};
+use crate::summary_report::SummaryReport;
+use crate::weekly_report::{PivotValue, WeeklyPivot, WeeklyReport};
+use chrono::{NaiveDate, TimeZone};
+use serde::Serialize;
+
+/// Styles of rendered tables.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TableStyle {
+    /// A GitHub flavored Markdown table, with group and total rows in bold
+    Markdown,
+    /// A table aligned with spaces for terminals
+    #[default]
+    Text,
+}
+
+/// Alignments of table columns
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Align {
+    Left,
+    Right,
+}
+
+/// Kinds of table rows, which are decorated differently by styles
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowKind {
+    /// A group, e.g. a project of a summary report
+    Group,
+    /// An item in the preceding group
+    Item,
+    /// A row without groups, e.g. a time entry of a detailed report
+    Entry,
+    /// The grand total
+    Total,
+}
+
+/// A table to be rendered in a `TableStyle`
+#[derive(Clone, Debug)]
+pub(crate) struct Table {
+    pub(crate) header: Vec<String>,
+    pub(crate) align: Vec<Align>,
+    pub(crate) rows: Vec<(RowKind, Vec<String>)>,
+}
+
+impl Table {
+    pub(crate) fn render(&self, style: TableStyle) -> String {
+        let header: Vec<String> = self.header.iter().map(|cell| decorate(style, None, 0, cell)).collect();
+        let rows: Vec<Vec<String>> = self.rows
+            .iter()
+            .map(|(kind, cells)| cells.iter()
+                .enumerate()
+                .map(|(i, cell)| decorate(style, Some(*kind), i, cell))
+                .collect())
+            .collect();
+
+        let mut widths: Vec<usize> = header.iter().map(|cell| width(cell)).collect();
+        for row in &rows {
+            for (width_, cell) in widths.iter_mut().zip(row) {
+                *width_ = (*width_).max(width(cell));
+            }
+        }
+        if style == TableStyle::Markdown {
+            widths.iter_mut().for_each(|width| *width = (*width).max(3));
+        }
+
+        let mut out = String::new();
+        self.push_row(&mut out, style, &widths, &header);
+        self.push_rule(&mut out, style, &widths);
+        for ((kind, _), row) in self.rows.iter().zip(&rows) {
+            if style == TableStyle::Text && *kind == RowKind::Total {
+                self.push_rule(&mut out, style, &widths);
+            }
+            self.push_row(&mut out, style, &widths, row);
+        }
+
+        out
+    }
+
+    fn push_row(&self, out: &mut String, style: TableStyle, widths: &[usize], cells: &[String]) {
+        let cells: Vec<String> = cells.iter()
+            .zip(widths)
+            .zip(&self.align)
+            .map(|((cell, width_), align)| {
+                let padding = " ".repeat(width_ - width(cell));
+                match align {
+                    Align::Left => format!("{}{}", cell, padding),
+                    Align::Right => format!("{}{}", padding, cell),
+                }
+            })
+            .collect();
+
+        match style {
+            TableStyle::Markdown => out.push_str(&format!("| {} |", cells.join(" | "))),
+            TableStyle::Text => out.push_str(cells.join("  ").trim_end()),
+        }
+        out.push('\n');
+    }
+
+    fn push_rule(&self, out: &mut String, style: TableStyle, widths: &[usize]) {
+        let rules: Vec<String> = widths.iter()
+            .zip(&self.align)
+            .map(|(width, align)| match (style, align) {
+                (TableStyle::Markdown, Align::Left) => format!(":{}", "-".repeat(width - 1)),
+                (TableStyle::Markdown, Align::Right) => format!("{}:", "-".repeat(width - 1)),
+                (TableStyle::Text, _) => "-".repeat(*width),
+            })
+            .collect();
+
+        match style {
+            TableStyle::Markdown => out.push_str(&format!("| {} |", rules.join(" | "))),
+            TableStyle::Text => out.push_str(&rules.join("  ")),
+        }
+        out.push('\n');
+    }
+}
+
+/// Escapes and decorates `cell` in column `column` of a row of `kind`, or of the header if `None`.
+fn decorate(style: TableStyle, kind: Option<RowKind>, column: usize, cell: &str) -> String {
+    match style {
+        TableStyle::Markdown => {
+            let cell = cell.replace('|', "\\|");
+            match kind {
+                Some(RowKind::Group) | Some(RowKind::Total) if !cell.is_empty() => format!("**{}**", cell),
+                Some(RowKind::Item) if column == 0 => format!("&emsp;{}", cell),
+                _ => cell,
+            }
+        }
+        TableStyle::Text => match kind {
+            Some(RowKind::Item) if column == 0 => format!("  {}", cell),
+            _ => cell.to_owned(),
+        },
+    }
+}
+
+fn width(cell: &str) -> usize {
+    cell.chars().count()
+}
+
+/// A renderer of reports as tables.
+///
+/// Durations are written as `h:mm` by default.
+#[derive(Clone, Debug)]
+pub struct TableRenderer {
+    style: TableStyle,
+    duration_format: DurationFormat,
+}
+
+impl Default for TableRenderer {
+    fn default() -> Self {
+        Self {
+            style: TableStyle::default(),
+            duration_format: DurationFormat::HoursMinutes,
+        }
+    }
+}
+
+impl TableRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn style(self, style: TableStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    pub fn duration_format(self, duration_format: DurationFormat) -> Self {
+        Self {
+            duration_format,
+            ..self
+        }
+    }
+
+    /// Renders a row for each group and its items, followed by the grand total.
+    pub fn render_summary(&self, report: &SummaryReport) -> String {
+        summary_table(report, self.duration_format).render(self.style)
+    }
+
+    /// Renders a row for each project or user, with a column for each date and the week total.
+    pub fn render_weekly_pivot(&self, pivot: &WeeklyPivot) -> String {
+        pivot_table(pivot, self.duration_format).render(self.style)
+    }
+}
+
+/// Returns a table of groups and items of `report` with the grand total.
+pub(crate) fn summary_table(report: &SummaryReport, duration_format: DurationFormat) -> Table {
+    let mut rows = Vec::new();
+    for data in &report.data {
+        rows.push((RowKind::Group, vec![data.title.name(), duration_format.format(&data.time)]));
+        for item in &data.items {
+            rows.push((RowKind::Item, vec![item.title.name(), duration_format.format(&item.time)]));
+        }
+    }
+    rows.push((RowKind::Total, vec![String::from("Total"), duration_format.format(&report.total_grand)]));
+
+    Table {
+        header: vec![String::from("Title"), String::from("Time")],
+        align: vec![Align::Left, Align::Right],
+        rows,
+    }
+}
+
+/// Pushes a group row for each data of a weekly report and item rows for its details.
+macro_rules! push_weekly_rows {
+    ($rows:ident, $report:expr, $cells:expr) => {
+        for data in &$report.data {
+            $rows.push((RowKind::Group, [vec![data.title.name()], $cells(&data.totals)].concat()));
+            for detail in &data.details {
+                $rows.push((RowKind::Item, [vec![detail.title.name()], $cells(&detail.totals)].concat()));
+            }
+        }
+    };
+}
+
+/// Returns a table of groups and details of `report`, with columns of `dates` and the week total.
+///
+/// The total row adds up group rows, for each currency in case of earnings.
+pub(crate) fn weekly_table(report: &WeeklyReport, dates: &[NaiveDate], duration_format: DurationFormat) -> Table {
+    let time_cells = |totals: &TimeTotals| -> Vec<String> {
+        totals.iter().map(|total| duration_format.format(total)).collect()
+    };
+    let time_total = |data: Vec<&TimeTotals>| -> Vec<String> {
+        let sums: Vec<String> = (0..8)
+            .map(|i| duration_format.format(&data.iter().map(|totals| *totals[i]).sum()))
+            .collect();
+        [vec![String::from("Total")], sums].concat()
+    };
+    let earning_cells = |totals: &EarningTotals| -> Vec<String> {
+        totals.amount.iter().map(|amount| format_amount(**amount, &totals.currency)).collect()
+    };
+    let earning_total = |data: Vec<&EarningTotals>| -> Vec<String> {
+        let sums: Vec<String> = (0..8)
+            .map(|i| {
+                let mut sums: Vec<(&Option<String>, f64)> = Vec::new();
+                for totals in &data {
+                    match sums.iter_mut().find(|(currency, _)| *currency == &totals.currency) {
+                        Some((_, sum)) => *sum += *totals.amount[i],
+                        None => sums.push((&totals.currency, *totals.amount[i])),
+                    }
+                }
+                sums.iter()
+                    .map(|(currency, sum)| format_amount(*sum, currency))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        [vec![String::from("Total")], sums].concat()
+    };
+
+    let mut rows = Vec::new();
+    let total = match report {
+        WeeklyReport::ProjectTimeWeeklyReport(report) => {
+            push_weekly_rows!(rows, report, time_cells);
+            time_total(report.data.iter().map(|data| &data.totals).collect())
+        }
+        WeeklyReport::UserTimeWeeklyReport(report) => {
+            push_weekly_rows!(rows, report, time_cells);
+            time_total(report.data.iter().map(|data| &data.totals).collect())
+        }
+        WeeklyReport::ProjectEarningWeeklyReport(report) => {
+            push_weekly_rows!(rows, report, earning_cells);
+            earning_total(report.data.iter().map(|data| &data.totals).collect())
+        }
+        WeeklyReport::UserEarningWeeklyReport(report) => {
+            push_weekly_rows!(rows, report, earning_cells);
+            earning_total(report.data.iter().map(|data| &data.totals).collect())
+        }
+    };
+    rows.push((RowKind::Total, total));
+
+    let mut header = vec![String::from("Title")];
+    header.extend(dates.iter().map(|date| date.format("%F").to_string()));
+    header.push(String::from("Total"));
+
+    Table {
+        header,
+        align: [vec![Align::Left], vec![Align::Right; 8]].concat(),
+        rows,
+    }
+}
+
+/// Returns a table of rows of `pivot` labelled with its dates.
+pub(crate) fn pivot_table(pivot: &WeeklyPivot, duration_format: DurationFormat) -> Table {
+    let rows = pivot.rows
+        .iter()
+        .map(|row| {
+            let mut cells = vec![row.title.clone()];
+            cells.extend(row.days.iter().map(|value| format_value(value, duration_format)));
+            cells.push(format_value(&row.total, duration_format));
+            (RowKind::Group, cells)
+        })
+        .collect();
+
+    let mut header = vec![String::from("Title")];
+    header.extend(pivot.dates.iter().map(|date| date.format("%F").to_string()));
+    header.push(String::from("Total"));
+
+    Table {
+        align: [vec![Align::Left], vec![Align::Right; header.len() - 1]].concat(),
+        header,
+        rows,
+    }
+}
+
+fn format_value(value: &PivotValue, duration_format: DurationFormat) -> String {
+    match value {
+        PivotValue::Time(time) => duration_format.format(time),
+        PivotValue::Earning { amount, currency } => format_amount(*amount, currency),
+    }
+}
+
+/// Returns a table of time entries of `report` with the grand total, dated in `timezone`.
+pub(crate) fn detailed_table<Tz: TimeZone>(report: &DetailedReport, timezone: &Tz, duration_format: DurationFormat) -> Table {
+    let text = |text: &Option<String>| text.clone().unwrap_or_default();
+    let mut rows: Vec<(RowKind, Vec<String>)> = report.data
+        .iter()
+        .map(|entry| (RowKind::Entry, vec![
+            entry.start.with_timezone(timezone).date_naive().format("%F").to_string(),
+            text(&entry.description),
+            text(&entry.project),
+            text(&entry.user),
+            duration_format.format(&entry.dur),
+            format_amount(*entry.billable, &entry.cur),
+        ]))
+        .collect();
+    rows.push((RowKind::Total, vec![
+        String::from("Total"),
+        String::new(),
+        String::new(),
+        String::new(),
+        duration_format.format(&report.total_grand),
+        format_currencies(&report.total_currencies),
+    ]));
+
+    Table {
+        header: ["Date", "Description", "Project", "User", "Duration", "Amount"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        align: vec![Align::Left, Align::Left, Align::Left, Align::Left, Align::Right, Align::Right],
+        rows,
+    }
+}
+
+/// Formats amounts of `currencies`, separated by commas.
+fn format_currencies(currencies: &[Currency]) -> String {
+    currencies.iter()
+        .map(|currency| format_amount(*currency.amount, &currency.currency))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats `amount` with two decimal places, followed by `currency` if any.
+fn format_amount(amount: f64, currency: &Option<String>) -> String {
+    match currency {
+        Some(currency) => format!("{:.2} {}", amount, currency),
+        None => format!("{:.2}", amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> SummaryReport {
+        let json = include_str!("../../tests/fixtures/summary/projects_time_entries.json");
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn render_summary_text() {
+        let ans = "\
+Title              Time
+-----------------  ----
+Toggl Development  3:00
+  Hard work        1:00
+  (none)           2:00
+(none)             3:00
+  Hard work        1:00
+  (none)           2:00
+-----------------  ----
+Total              6:00
+";
+        assert_eq!(TableRenderer::new().render_summary(&report()), ans);
+    }
+
+    #[test]
+    fn render_summary_markdown() {
+        let renderer = TableRenderer::new()
+            .style(TableStyle::Markdown)
+            .duration_format(DurationFormat::DecimalHours);
+        let ans = "\
+| Title                 |     Time |
+| :-------------------- | -------: |
+| **Toggl Development** | **3.00** |
+| &emsp;Hard work       |     1.00 |
+| &emsp;(none)          |     2.00 |
+| **(none)**            | **3.00** |
+| &emsp;Hard work       |     1.00 |
+| &emsp;(none)          |     2.00 |
+| **Total**             | **6.00** |
+";
+        assert_eq!(renderer.render_summary(&report()), ans);
+    }
+
+    #[test]
+    fn render_weekly_pivot_text() {
+        use crate::query::WeeklyQuery;
+        use chrono::NaiveDate;
+
+        let json = include_str!("../../tests/fixtures/weekly/projects_time.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let query = WeeklyQuery::new(334).since_date(NaiveDate::from_ymd_opt(2021, 2, 26).unwrap());
+
+        let ans = "\
+Title              2021-02-26  2021-02-27  2021-02-28  2021-03-01  2021-03-02  2021-03-03  2021-03-04  Total
+-----------------  ----------  ----------  ----------  ----------  ----------  ----------  ----------  -----
+Toggl Development        0:00        1:00        0:00        0:30        0:00        0:00        0:00   1:30
+(none)                   0:00        2:00        0:00        0:30        0:00        0:00        0:00   2:30
+";
+        assert_eq!(TableRenderer::new().render_weekly_pivot(&report.pivot(&query)), ans);
+    }
+
+    #[test]
+    fn markdown_escape() {
+        let table = Table {
+            header: vec![String::from("A|B")],
+            align: vec![Align::Left],
+            rows: vec![(RowKind::Item, vec![String::from("x")])],
+        };
+        assert_eq!(table.render(TableStyle::Markdown), "| A\\|B    |\n| :------ |\n| &emsp;x |\n");
+    }
+}
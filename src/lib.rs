@@ -6,10 +6,10 @@
 //! # examples
 //!
 //! To get summary reports of Toggl Track,
-//! create struct `Query` and pass it to `Toggl.get_summary_report()`.
+//! create struct `SummaryQuery` and pass it to `Toggl.get_summary_report()`.
 //!
 //! ```no_run
-//! use toggl_reports::{Toggl, query::Query, query::GroupingKind, query::OrderKind};
+//! use toggl_reports::{Toggl, query::SummaryQuery, query::SummaryGroupingKind, query::SummaryOrderKind};
 //! use chrono::Utc;
 //!
 //! #[tokio::main]
@@ -18,10 +18,10 @@
 //!     let workspace_id: u64 = 264;                               // your workspace id
 //!
 //!     let toggl = Toggl::new(api_token);
-//!     let query = Query::new(workspace_id)
-//!         .grouping(GroupingKind::Projects)
-//!         .order_field(OrderKind::Description)
-//!         .since(Utc::today());
+//!     let query = SummaryQuery::new(workspace_id)
+//!         .grouping(SummaryGroupingKind::Projects)
+//!         .order_field(SummaryOrderKind::Title)
+//!         .since_date(Utc::now().date_naive());
 //!
 //!     let res = toggl.get_summary_report(&query).await.unwrap();
 //!     println!("TOTAL: {}s", res.total_grand.num_seconds());
@@ -34,84 +34,632 @@
 #[macro_use]
 extern crate enum_display_derive;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+pub mod date_range;
 pub mod detailed_report;
+pub mod error;
+pub mod export;
 pub mod query;
+pub mod rate_limit;
+pub mod retry;
 pub mod reports;
 pub mod summary_report;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
 pub mod weekly_report;
 
-use anyhow::Result;
-use query::Query;
-use reqwest::Client;
-pub use detailed_report::DetailedReport;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use query::{DetailedQuery, FileFormat, Query, ReportQuery, SummaryQuery, WeeklyQuery};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use transport::{BasicAuth, BodyStream, Transport};
+pub use builder::TogglBuilder;
+pub use detailed_report::{DataEntry, DetailedReport};
+pub use error::{ApiError, Error, Result};
+pub use rate_limit::RateLimiter;
+pub use retry::RetryPolicy;
 pub use summary_report::SummaryReport;
 pub use weekly_report::WeeklyReport;
 
-const DEFAULT_UA: &str = "komori-n<ikamat.kmr@gmail.com>";
-
 /// A structure to wrap Toggl Reports API v2.
-#[derive(Debug)]
+///
+/// Cloning `Toggl` is cheap, and clones share the same transport and rate limiter.
+#[derive(Clone, Debug)]
 pub struct Toggl {
-    api_token: String,
+    auth: BasicAuth,
     user_agent: String,
+    base_url: String,
+    transport: Arc<dyn Transport>,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
 }
 
 impl Toggl {
     pub fn new(api_token: &str) -> Self {
-        Self {
-            api_token: api_token.to_owned(),
-            user_agent: DEFAULT_UA.to_owned(),
-        }
+        TogglBuilder::new(api_token).build()
     }
 
     pub fn with_user_agent(api_token: &str, user_agent: &str) -> Self {
-        Self {
-            api_token: api_token.to_owned(),
-            user_agent: user_agent.to_owned(),
-        }
+        TogglBuilder::new(api_token)
+            .user_agent(user_agent)
+            .build()
+    }
+
+    /// Returns a builder to configure the user agent, the base URL and so on.
+    pub fn builder(api_token: &str) -> TogglBuilder {
+        TogglBuilder::new(api_token)
     }
 
     async fn rest_get(&self, endpoint: &str, query: &Query) -> Result<String> {
-        let client = Client::new();
+        let body = self.rest_get_bytes(endpoint, query).await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    async fn rest_get_bytes(&self, endpoint: &str, query: &Query) -> Result<Vec<u8>> {
+        self.with_retry(|| self.rest_get_once(endpoint, query)).await
+    }
+
+    /// Calls `f` until it succeeds or `retry_policy` gives up.
+    async fn with_retry<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        retry::retry(self.retry_policy.as_ref(), f, tokio::time::sleep).await
+    }
+
+    /// Returns the URL and parameters of a request, waiting for the rate limiter.
+    async fn prepare(&self, endpoint: &str, query: &Query) -> (String, Vec<(&'static str, String)>) {
+        let url = format!("{}/{}", self.base_url, endpoint);
         let mut query_vec = query.to_vec();
         query_vec.push(("user_agent", self.user_agent.clone()));
 
-        let req = client
-            .get(endpoint)
-            .query(&query_vec)
-            .basic_auth(&self.api_token, Some("api_token"));
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        (url, query_vec)
+    }
 
-        let res = req
-            .send().await?
-            .text().await?;
+    async fn rest_get_once(&self, endpoint: &str, query: &Query) -> Result<Vec<u8>> {
+        let (url, query_vec) = self.prepare(endpoint, query).await;
+        let res = self.transport.get(&url, &query_vec, &self.auth).await?;
+        if !res.status.is_success() {
+            return Err(error::from_response(res.status, &res.headers, &res.body));
+        }
 
-        Ok(res)
+        Ok(res.body)
     }
 
-    pub async fn get_weekly_report(&self, query: &Query) -> Result<WeeklyReport> {
-        const ENDPOINT: &str = "https://api.track.toggl.com/reports/api/v2/weekly";
+    async fn rest_stream_once(&self, endpoint: &str, query: &Query) -> Result<BodyStream> {
+        let (url, query_vec) = self.prepare(endpoint, query).await;
+        let res = self.transport.get_stream(&url, &query_vec, &self.auth).await?;
+        if !res.status.is_success() {
+            let body: Vec<u8> = res.body.try_concat().await?;
+            return Err(error::from_response(res.status, &res.headers, &body));
+        }
 
-        let res = self.rest_get(ENDPOINT, query).await?;
-        let report: WeeklyReport = serde_json::from_str(&res)?;
+        Ok(res.body)
+    }
 
-        Ok(report)
+    /// Fetches a weekly report, decoded as the variant which `grouping` and `calculate` of `query` request.
+    pub async fn get_weekly_report(&self, query: &WeeklyQuery) -> Result<WeeklyReport> {
+        const ENDPOINT: &str = "weekly";
+
+        query.validate()?;
+        let query = query.as_ref();
+        let res = self.rest_get(ENDPOINT, query).await?;
+        error::decode_seed(res, weekly_report::WeeklyReportSeed(query))
     }
 
-    pub async fn get_detailed_report(&self, query: &Query) -> Result<DetailedReport> {
-        const ENDPOINT: &str = "https://api.track.toggl.com/reports/api/v2/details";
+    pub async fn get_detailed_report(&self, query: &DetailedQuery) -> Result<DetailedReport> {
+        const ENDPOINT: &str = "details";
 
+        query.validate()?;
+        let query = query.as_ref();
         let res = self.rest_get(ENDPOINT, query).await?;
-        let report: DetailedReport = serde_json::from_str(&res)?;
+        error::decode(res)
+    }
+
+    /// Fetches all pages of a detailed report and merges them into one `DetailedReport`.
+    ///
+    /// The first page is fetched alone to learn the number of pages, and the rest are
+    /// fetched with at most `concurrency` requests in flight. Entries keep the page order.
+    /// The `page` parameter of `query` is ignored.
+    pub async fn get_all_detailed_report(&self, query: &DetailedQuery, concurrency: usize) -> Result<DetailedReport> {
+        let mut report = self.get_detailed_report(&query.clone().page(1)).await?;
+
+        let pages: Vec<DetailedReport> = stream::iter(2..=report.page_count())
+            .map(|page| {
+                let query = query.clone().page(page);
+                async move { self.get_detailed_report(&query).await }
+            })
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await?;
+        pages.into_iter()
+            .for_each(|page| report.data.extend(page.data));
 
         Ok(report)
     }
 
-    pub async fn get_summary_report(&self, query: &Query) -> Result<SummaryReport> {
-        const ENDPOINT: &str = "https://api.track.toggl.com/reports/api/v2/summary";
+    /// Fetches all pages of a detailed report over any date range and merges them.
+    ///
+    /// The range from `since` to `until` is split into windows of at most one year,
+    /// which is the longest range the API accepts. See `get_all_detailed_report()` for `concurrency`.
+    pub async fn get_all_detailed_report_by_year(&self, query: &DetailedQuery, concurrency: usize) -> Result<DetailedReport> {
+        let mut merged: Option<DetailedReport> = None;
+        for window in query.split_by_year() {
+            let report = self.get_all_detailed_report(&window, concurrency).await?;
+            match &mut merged {
+                Some(merged) => merged.merge(report),
+                None => merged = Some(report),
+            }
+        }
+
+        Ok(merged.expect("split_by_year() returns at least one query"))
+    }
 
+    /// Returns a stream of all entries of a detailed report.
+    ///
+    /// Pages are fetched lazily, starting from page 1, until `total_count` entries
+    /// are yielded. The `page` parameter of `query` is ignored.
+    pub fn detailed_entries<'a>(&'a self, query: &'a DetailedQuery) -> impl Stream<Item = Result<DataEntry>> + 'a {
+        stream::try_unfold(Some(1), move |page| async move {
+            match page {
+                Some(page) => self.get_detailed_page(query, page).await
+                    .map(|(entries, next)| Some((stream::iter(entries.into_iter().map(Ok)), next))),
+                None => Ok(None),
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Fetches `page` of a detailed report and returns its entries with the next page number, if any.
+    async fn get_detailed_page(&self, query: &DetailedQuery, page: u64) -> Result<(Vec<DataEntry>, Option<u64>)> {
+        let report = self.get_detailed_report(&query.clone().page(page)).await?;
+        let next = if report.data.is_empty() || page >= report.page_count() {
+            None
+        } else {
+            Some(page + 1)
+        };
+
+        Ok((report.data, next))
+    }
+
+    /// Fetches a summary report over any date range.
+    ///
+    /// The range from `since` to `until` is split into windows of at most one year,
+    /// which is the longest range the API accepts, and their reports are merged.
+    pub async fn get_summary_report_by_year(&self, query: &SummaryQuery) -> Result<SummaryReport> {
+        let mut merged: Option<SummaryReport> = None;
+        for window in query.split_by_year() {
+            let report = self.get_summary_report(&window).await?;
+            match &mut merged {
+                Some(merged) => merged.merge(report),
+                None => merged = Some(report),
+            }
+        }
+
+        Ok(merged.expect("split_by_year() returns at least one query"))
+    }
+
+    /// Fetches a summary report, with titles decoded as `grouping` and `subgrouping` of `query` request.
+    pub async fn get_summary_report(&self, query: &SummaryQuery) -> Result<SummaryReport> {
+        const ENDPOINT: &str = "summary";
+
+        query.validate()?;
+        let query = query.as_ref();
         let res = self.rest_get(ENDPOINT, query).await?;
-        let report: SummaryReport = serde_json::from_str(&res)?;
+        error::decode_seed(res, summary_report::SummaryReportSeed(query))
+    }
 
-        Ok(report)
+    /// Downloads a report file, e.g. `summary.pdf` or `details.csv`, as raw bytes.
+    ///
+    /// The kind of the report is determined by the type of `query`, which is validated
+    /// as the JSON endpoints do.
+    pub async fn get_report_file<Q: ReportQuery>(&self, format: FileFormat, query: &Q) -> Result<Vec<u8>> {
+        let endpoint = report_file_endpoint(format, query)?;
+        self.rest_get_bytes(&endpoint, query.as_ref()).await
+    }
+
+    /// Downloads a report file like `get_report_file()` and streams it to `writer` chunk by chunk.
+    ///
+    /// Requests are retried until the response starts. Once chunks are written to `writer`,
+    /// failures are returned without retrying.
+    pub async fn write_report_file<Q, W>(&self, format: FileFormat, query: &Q, writer: &mut W) -> Result<()>
+    where
+        Q: ReportQuery,
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let endpoint = report_file_endpoint(format, query)?;
+        let mut body = self.with_retry(|| self.rest_stream_once(&endpoint, query.as_ref())).await?;
+        while let Some(chunk) = body.try_next().await? {
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+
+        Ok(())
+    }
+}
+
+/// Validates `query` and returns the endpoint of its report file in `format`.
+fn report_file_endpoint<Q: ReportQuery>(format: FileFormat, query: &Q) -> Result<String> {
+    query.as_ref().validate(Q::KIND)?;
+    Ok(format!("{}.{}", Q::KIND.endpoint(), format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+    use wiremock::matchers::{basic_auth, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const API_TOKEN: &str = "00000000000000000000000000000000";
+
+    fn toggl(server: &MockServer) -> Toggl {
+        Toggl::builder(API_TOKEN)
+            .base_url(&server.uri())
+            .build()
+    }
+
+    fn detailed_page(ids: &[u64], total_count: u64, per_page: u64) -> serde_json::Value {
+        let data: Vec<_> = ids.iter()
+            .map(|id| serde_json::json!({
+                "id": id,
+                "pid": null,
+                "tid": null,
+                "uid": 264,
+                "description": "desc",
+                "start": "2021-02-28T10:00:00+00:00",
+                "end": "2021-02-28T11:00:00+00:00",
+                "dur": 3600000,
+                "user": "komori-n",
+                "use_stop": true,
+                "client": null,
+                "project": null,
+                "task": null,
+                "billable": null,
+                "is_billable": false,
+                "cur": null,
+                "tags": []
+            }))
+            .collect();
+
+        serde_json::json!({
+            "total_grand": 3600000 * total_count,
+            "total_billable": null,
+            "total_count": total_count,
+            "per_page": per_page,
+            "total_currencies": [],
+            "data": data
+        })
+    }
+
+    async fn mount_detailed_pages(server: &MockServer) {
+        let pages: [&[u64]; 3] = [&[1, 2], &[3, 4], &[5]];
+        for (i, ids) in pages.iter().enumerate() {
+            Mock::given(method("GET"))
+                .and(path("/details"))
+                .and(query_param("page", (i + 1).to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(detailed_page(ids, 5, 2)))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn summary_report_basic() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .and(query_param("workspace_id", "334"))
+            .and(query_param("user_agent", "test-agent"))
+            .and(basic_auth(API_TOKEN, "api_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_grand": 334000,
+                "total_billable": null,
+                "total_currencies": [{"currency": null, "amount": null}],
+                "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let toggl = Toggl::builder(API_TOKEN)
+            .user_agent("test-agent")
+            .base_url(&format!("{}/", server.uri()))
+            .build();
+        let report = toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap();
+
+        assert_eq!(report.total_grand.num_seconds(), 334);
+        assert!(report.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn retry_server_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_grand": 334000,
+                "total_billable": null,
+                "total_currencies": [],
+                "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let toggl = Toggl::builder(API_TOKEN)
+            .base_url(&server.uri())
+            .retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1)))
+            .build();
+        let report = toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap();
+
+        assert_eq!(report.total_grand.num_seconds(), 334);
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_on_auth_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let toggl = Toggl::builder(API_TOKEN)
+            .base_url(&server.uri())
+            .retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1)))
+            .build();
+        let err = toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap_err();
+
+        assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+    }
+
+    #[tokio::test]
+    async fn custom_client() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        use wiremock::matchers::header;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .and(header("x-custom", "hoge"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_grand": null,
+                "total_billable": null,
+                "total_currencies": [],
+                "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-custom", HeaderValue::from_static("hoge"));
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let toggl = Toggl::builder(API_TOKEN)
+            .base_url(&server.uri())
+            .client(client)
+            .build();
+
+        toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        use async_trait::async_trait;
+        use std::sync::Mutex;
+        use transport::Response;
+
+        type Request = (String, Vec<(&'static str, String)>, BasicAuth);
+
+        #[derive(Debug, Default)]
+        struct FakeTransport {
+            requests: Arc<Mutex<Vec<Request>>>,
+        }
+
+        #[async_trait]
+        impl Transport for FakeTransport {
+            async fn get(&self, url: &str, query: &[(&'static str, String)], auth: &BasicAuth) -> Result<Response> {
+                self.requests.lock().unwrap().push((url.to_owned(), query.to_vec(), auth.clone()));
+                Ok(Response::new(StatusCode::OK, r#"{"total_grand": 334000, "total_billable": null, "total_currencies": [], "data": []}"#))
+            }
+        }
+
+        let transport = FakeTransport::default();
+        let requests = transport.requests.clone();
+        let toggl = Toggl::builder(API_TOKEN)
+            .user_agent("test-agent")
+            .transport(transport)
+            .build();
+        let report = toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap();
+
+        assert_eq!(report.total_grand.num_seconds(), 334);
+        assert_eq!(*requests.lock().unwrap(), vec![(
+            String::from("https://api.track.toggl.com/reports/api/v2/summary"),
+            vec![("workspace_id", String::from("334")), ("user_agent", String::from("test-agent"))],
+            BasicAuth::api_token(API_TOKEN),
+        )]);
+    }
+
+    #[tokio::test]
+    async fn detailed_entries_all_pages() {
+        let server = MockServer::start().await;
+        mount_detailed_pages(&server).await;
+
+        let toggl = toggl(&server);
+        let query = DetailedQuery::new(334).page(3);
+        let entries: Vec<DataEntry> = toggl.detailed_entries(&query).try_collect().await.unwrap();
+        let ids: Vec<u64> = entries.iter().map(|entry| entry.id).collect();
+
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn all_detailed_report_merges_pages() {
+        let server = MockServer::start().await;
+        mount_detailed_pages(&server).await;
+
+        let toggl = toggl(&server);
+        let report = toggl.get_all_detailed_report(&DetailedQuery::new(334), 2).await.unwrap();
+
+        let ids: Vec<u64> = report.data.iter().map(|entry| entry.id).collect();
+
+        assert_eq!(report.total_count, 5);
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn summary_report_by_year() {
+        use chrono::NaiveDate;
+
+        let server = MockServer::start().await;
+        for (since, hours) in [("2020-01-01", 1), ("2021-01-01", 2)] {
+            Mock::given(method("GET"))
+                .and(path("/summary"))
+                .and(query_param("since", since))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "total_grand": 3600000 * hours,
+                    "total_billable": null,
+                    "total_currencies": [],
+                    "data": []
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let date = |s| NaiveDate::parse_from_str(s, "%F").unwrap();
+        let query = SummaryQuery::new(334)
+            .since_date(date("2020-01-01"))
+            .until_date(date("2021-06-30"));
+        let report = toggl(&server).get_summary_report_by_year(&query).await.unwrap();
+
+        assert_eq!(report.total_grand.num_hours(), 3);
+    }
+
+    #[tokio::test]
+    async fn api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/weekly"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "error": {"message": "Unauthorized", "tip": null, "code": 403}
+            })))
+            .mount(&server)
+            .await;
+
+        let err = toggl(&server).get_weekly_report(&WeeklyQuery::new(334)).await.unwrap_err();
+
+        match err {
+            Error::Api { status, error } => {
+                assert_eq!(status, StatusCode::FORBIDDEN);
+                assert_eq!(error.message, "Unauthorized");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_query() {
+        use query::{GroupingKind, QueryProblem};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let query = SummaryQuery::new(334).subgrouping(GroupingKind::Projects);
+        let err = toggl(&server).get_summary_report(&query).await.unwrap_err();
+
+        match err {
+            Error::Invalid(err) => assert_eq!(err.problems, vec![QueryProblem::SameGrouping(GroupingKind::Projects)]),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn report_file() {
+        let pdf: Vec<u8> = (0..1 << 20).map(|i| (i % 251) as u8).collect();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary.pdf"))
+            .and(query_param("workspace_id", "334"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(pdf.clone()))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let toggl = toggl(&server);
+        let query = SummaryQuery::new(334);
+        let bytes = toggl.get_report_file(FileFormat::Pdf, &query).await.unwrap();
+        assert_eq!(bytes, pdf);
+
+        let mut written = Vec::new();
+        toggl.write_report_file(FileFormat::Pdf, &query, &mut written).await.unwrap();
+        assert_eq!(written, pdf);
+    }
+
+    #[tokio::test]
+    async fn report_file_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/details.csv"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "error": {"message": "not found", "tip": null, "code": 404}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut written = Vec::new();
+        let err = toggl(&server)
+            .write_report_file(FileFormat::Csv, &DetailedQuery::new(334), &mut written)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert!(matches!(err, Error::Api { .. }));
+        assert!(written.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rate_limited() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3"))
+            .mount(&server)
+            .await;
+
+        let err = toggl(&server).get_summary_report(&SummaryQuery::new(334)).await.unwrap_err();
+
+        match err {
+            Error::RateLimited { retry_after } => assert_eq!(retry_after, Some(Duration::from_secs(3))),
+            other => panic!("unexpected error: {:?}", other),
+        }
     }
 }
@@ -2,20 +2,23 @@
 //!
 //! For more details, see https://github.com/toggl/toggl_api_docs/blob/master/reports.md.
 
-use chrono::{Date, Utc};
+use crate::date_range::{Calendar, DateRange};
+#[allow(deprecated)]
+use chrono::Date;
+use chrono::{DateTime, Months, NaiveDate, TimeZone, Utc};
 use itertools::Itertools;
 use std::fmt::{self, Display};
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Query {
     workspace_id: u64,
-    grouping: Option<GroupingKind>,
-    subgrouping: Option<GroupingKind>,
+    pub(crate) grouping: Option<GroupingKind>,
+    pub(crate) subgrouping: Option<GroupingKind>,
     subgrouping_ids: Option<bool>,
     grouped_time_entry_ids: Option<bool>,
-    calculate: Option<CalculateKind>,
-    since: Option<Date<Utc>>,
-    until: Option<Date<Utc>>,
+    pub(crate) calculate: Option<CalculateKind>,
+    pub(crate) since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
     page: Option<u64>,
     billable: Option<BillableKind>,
     client_ids: Option<Vec<u64>>,
@@ -37,13 +40,16 @@ pub struct Query {
 
 macro_rules! define_setter {
     ($mem:ident, $type:ty) => {
-        pub fn $mem(self, val: $type) -> Self {
+        define_setter!($mem, $mem, $type);
+    };
+    ($name:ident, $mem:ident, $type:ty) => {
+        pub fn $name(self, val: $type) -> Self {
             Self {
                 $mem: Some(val),
                 ..self
             }
         }
-    }
+    };
 }
 
 macro_rules! push_query {
@@ -85,8 +91,8 @@ impl Query {
     define_setter!(subgrouping_ids, bool);
     define_setter!(grouped_time_entry_ids, bool);
     define_setter!(calculate, CalculateKind);
-    define_setter!(since, Date<Utc>);
-    define_setter!(until, Date<Utc>);
+    define_setter!(since_date, since, NaiveDate);
+    define_setter!(until_date, until, NaiveDate);
     define_setter!(page, u64);
     define_setter!(billable, BillableKind);
     define_setter!(client_ids, Vec<u64>);
@@ -105,6 +111,75 @@ impl Query {
     define_setter!(rounding, bool);
     define_setter!(display_hours, DisplayHoursKind);
 
+    /// Sets `since` to the calendar date of `since` in its own timezone.
+    pub fn since_in<Tz: TimeZone>(self, since: DateTime<Tz>) -> Self {
+        self.since_date(since.date_naive())
+    }
+
+    /// Sets `until` to the calendar date of `until` in its own timezone.
+    pub fn until_in<Tz: TimeZone>(self, until: DateTime<Tz>) -> Self {
+        self.until_date(until.date_naive())
+    }
+
+    #[deprecated(note = "`Date` is deprecated in chrono; use `since_date` or `since_in` instead")]
+    #[allow(deprecated)]
+    pub fn since(self, since: Date<Utc>) -> Self {
+        self.since_date(since.naive_utc())
+    }
+
+    #[deprecated(note = "`Date` is deprecated in chrono; use `until_date` or `until_in` instead")]
+    #[allow(deprecated)]
+    pub fn until(self, until: Date<Utc>) -> Self {
+        self.until_date(until.naive_utc())
+    }
+
+    /// Sets `since` and `until` to `range`.
+    pub fn date_range(self, range: DateRange) -> Self {
+        self.since_date(range.since)
+            .until_date(range.until)
+    }
+
+    /// Sets the current week from Monday in UTC. Use `Calendar` for other timezones or first weekdays.
+    pub fn this_week(self) -> Self {
+        self.date_range(Calendar::default().this_week())
+    }
+
+    /// Sets the previous week from Monday in UTC.
+    pub fn last_week(self) -> Self {
+        self.date_range(Calendar::default().last_week())
+    }
+
+    /// Sets the current month in UTC.
+    pub fn this_month(self) -> Self {
+        self.date_range(Calendar::default().this_month())
+    }
+
+    /// Sets the previous month in UTC.
+    pub fn last_month(self) -> Self {
+        self.date_range(Calendar::default().last_month())
+    }
+
+    /// Sets the range from January 1st to today in UTC.
+    pub fn year_to_date(self) -> Self {
+        self.date_range(Calendar::default().year_to_date())
+    }
+
+    /// Sets the last `n` days including today in UTC. `n` of 0 is treated as 1.
+    pub fn last_n_days(self, n: u32) -> Self {
+        self.date_range(Calendar::default().last_n_days(n))
+    }
+
+    /// Sets the `quarter`-th quarter of `year`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `quarter` is in 1..=4.
+    pub fn quarter(self, year: i32, quarter: u32) -> Self {
+        let range = DateRange::quarter(year, quarter)
+            .unwrap_or_else(|| panic!("quarter must be in 1..=4, but {}", quarter));
+        self.date_range(range)
+    }
+
     pub fn to_vec(&self) -> Vec<(&'static str, String)> {
         let mut query_vec = Vec::new();
 
@@ -143,9 +218,187 @@ impl Query {
 
         query_vec
     }
+
+    /// Splits the query into windows of at most one year from `since` to `until`.
+    ///
+    /// Returns a clone of the query itself if `since` or `until` is not set,
+    /// or if `since` is later than `until`.
+    pub fn split_by_year(&self) -> Vec<Self> {
+        let (since, until) = match (&self.since, &self.until) {
+            (Some(since), Some(until)) if since <= until => (*since, *until),
+            _ => return vec![self.clone()],
+        };
+
+        let mut windows = Vec::new();
+        let mut start = since;
+        loop {
+            let next = start.checked_add_months(Months::new(12));
+            let end = last_day_of_year(start).map_or(until, |end| end.min(until));
+            windows.push(self.clone()
+                .since_date(start)
+                .until_date(end));
+
+            match next {
+                Some(next) if end < until => start = next,
+                _ => return windows,
+            }
+        }
+    }
+
+    /// Checks documented constraints of query parameters for `report`.
+    ///
+    /// All problems found are returned at once.
+    pub fn validate(&self, report: ReportKind) -> Result<(), ValidationError> {
+        let mut problems = Vec::new();
+
+        if let (Some(since), Some(until)) = (&self.since, &self.until) {
+            if since > until {
+                problems.push(QueryProblem::SinceAfterUntil);
+            } else if last_day_of_year(*since).is_some_and(|last| until > &last) {
+                problems.push(QueryProblem::RangeTooLong);
+            }
+        }
+
+        if let Some(grouping) = &self.grouping {
+            let valid = match report {
+                ReportKind::Weekly => matches!(grouping, GroupingKind::Projects | GroupingKind::Users),
+                ReportKind::Summary => matches!(grouping, GroupingKind::Projects | GroupingKind::Clients | GroupingKind::Users),
+                ReportKind::Detailed => true,
+            };
+            if !valid {
+                problems.push(QueryProblem::InvalidGrouping(grouping.clone()));
+            }
+        }
+
+        if report == ReportKind::Summary {
+            let grouping = self.grouping.as_ref().unwrap_or(&GroupingKind::Projects);
+            let subgrouping = self.subgrouping.as_ref().unwrap_or(&GroupingKind::TimeEntries);
+            if grouping == subgrouping {
+                problems.push(QueryProblem::SameGrouping(grouping.clone()));
+            }
+        }
+
+        if let Some(order_field) = &self.order_field {
+            let valid = match report {
+                ReportKind::Weekly => matches!(order_field,
+                    OrderKind::Title | OrderKind::Day1 | OrderKind::Day2 | OrderKind::Day3 | OrderKind::Day4 |
+                    OrderKind::Day5 | OrderKind::Day6 | OrderKind::Day7 | OrderKind::WeekTotal),
+                ReportKind::Detailed => matches!(order_field,
+                    OrderKind::Date | OrderKind::Description | OrderKind::Duration | OrderKind::User),
+                ReportKind::Summary => matches!(order_field,
+                    OrderKind::Title | OrderKind::Duration | OrderKind::Amount),
+            };
+            if !valid {
+                problems.push(QueryProblem::InvalidOrderField(order_field.clone()));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { report, problems })
+        }
+    }
+}
+
+/// Returns the last day of the year from `since`, i.e. the day before `since` plus 12 months.
+///
+/// Both `Query::validate()` and `Query::split_by_year()` use it as the longest range.
+fn last_day_of_year(since: NaiveDate) -> Option<NaiveDate> {
+    since.checked_add_months(Months::new(12))
+        .and_then(|next| next.pred_opt())
+}
+
+/// Kinds of reports
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReportKind {
+    Weekly,
+    Detailed,
+    Summary,
+}
+
+impl fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportKind::Weekly => write!(f, "weekly"),
+            ReportKind::Detailed => write!(f, "detailed"),
+            ReportKind::Summary => write!(f, "summary"),
+        }
+    }
+}
+
+impl ReportKind {
+    /// Returns the endpoint of the report, relative to the base URL.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            ReportKind::Weekly => "weekly",
+            ReportKind::Detailed => "details",
+            ReportKind::Summary => "summary",
+        }
+    }
+}
+
+/// Formats of report files served by the API
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Pdf,
+    Csv,
+    Xlsx,
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileFormat::Pdf => write!(f, "pdf"),
+            FileFormat::Csv => write!(f, "csv"),
+            FileFormat::Xlsx => write!(f, "xlsx"),
+        }
+    }
+}
+
+/// A problem of a query found by `Query::validate()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryProblem {
+    /// `since` is later than `until`
+    SinceAfterUntil,
+    /// `until` is on or after `since` plus 12 months, i.e. the range is longer than one year
+    RangeTooLong,
+    /// `grouping` is not accepted by the report
+    InvalidGrouping(GroupingKind),
+    /// `grouping` and `subgrouping` are the same
+    SameGrouping(GroupingKind),
+    /// `order_field` is not accepted by the report
+    InvalidOrderField(OrderKind),
+}
+
+impl fmt::Display for QueryProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryProblem::SinceAfterUntil => write!(f, "since is later than until"),
+            QueryProblem::RangeTooLong => write!(f, "date range is longer than one year"),
+            QueryProblem::InvalidGrouping(grouping) => write!(f, "grouping {} is not supported", grouping),
+            QueryProblem::SameGrouping(grouping) => write!(f, "grouping and subgrouping are both {}", grouping),
+            QueryProblem::InvalidOrderField(order_field) => write!(f, "order_field {} is not supported", order_field),
+        }
+    }
+}
+
+/// An error returned by `Query::validate()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    pub report: ReportKind,
+    pub problems: Vec<QueryProblem>,
 }
 
-#[derive(Debug)]
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {} report query: {}", self.report, self.problems.iter().join("; "))
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GroupingKind {
     Projects,
     Clients,
@@ -166,20 +419,20 @@ impl fmt::Display for GroupingKind {
     }
 }
 
-#[derive(Debug, Display)]
+#[derive(Clone, Debug, Display)]
 pub enum CalculateKind {
     Time,
     Earnings
 }
 
-#[derive(Debug, Display)]
+#[derive(Clone, Debug, Display)]
 pub enum BillableKind {
     Yes,
     No,
     Both,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum OrderKind {
     Date,
     Description,
@@ -218,12 +471,319 @@ impl fmt::Display for OrderKind {
     }
 }
 
-#[derive(Debug, Display)]
+#[derive(Clone, Debug, Display)]
 pub enum DisplayHoursKind {
     Decimal,
     Minites,
 }
 
+macro_rules! define_typed_setter {
+    ($mem:ident, $type:ty) => {
+        pub fn $mem(self, val: $type) -> Self {
+            Self(self.0.$mem(val))
+        }
+    };
+    ($mem:ident, $type:ty, $into:ty) => {
+        pub fn $mem(self, val: $type) -> Self {
+            Self(self.0.$mem(<$into>::from(val)))
+        }
+    };
+}
+
+/// Defines setters of parameters shared by all endpoints
+macro_rules! define_common_setters {
+    () => {
+        pub fn new(workspace_id: u64) -> Self {
+            Self(Query::new(workspace_id))
+        }
+
+        define_typed_setter!(since_date, NaiveDate);
+
+        pub fn since_in<Tz: TimeZone>(self, since: DateTime<Tz>) -> Self {
+            Self(self.0.since_in(since))
+        }
+
+        define_typed_setter!(billable, BillableKind);
+        define_typed_setter!(client_ids, Vec<u64>);
+        define_typed_setter!(project_ids, Vec<u64>);
+        define_typed_setter!(user_ids, Vec<u64>);
+        define_typed_setter!(members_of_group_ids, Vec<u64>);
+        define_typed_setter!(or_members_of_group_ids, Vec<u64>);
+        define_typed_setter!(tag_ids, Vec<u64>);
+        define_typed_setter!(task_ids, Vec<u64>);
+        define_typed_setter!(time_entry_ids, Vec<u64>);
+        define_typed_setter!(description, String);
+        define_typed_setter!(without_description, bool);
+        define_typed_setter!(order_desc, bool);
+        define_typed_setter!(distinct_rates, bool);
+        define_typed_setter!(rounding, bool);
+        define_typed_setter!(display_hours, DisplayHoursKind);
+
+        pub fn to_vec(&self) -> Vec<(&'static str, String)> {
+            self.0.to_vec()
+        }
+    };
+}
+
+/// Defines setters of `until` and date range presets for endpoints which accept any range
+macro_rules! define_range_setters {
+    () => {
+        define_typed_setter!(until_date, NaiveDate);
+
+        pub fn until_in<Tz: TimeZone>(self, until: DateTime<Tz>) -> Self {
+            Self(self.0.until_in(until))
+        }
+
+        define_typed_setter!(date_range, DateRange);
+        define_typed_setter!(last_n_days, u32);
+
+        pub fn this_week(self) -> Self {
+            Self(self.0.this_week())
+        }
+
+        pub fn last_week(self) -> Self {
+            Self(self.0.last_week())
+        }
+
+        pub fn this_month(self) -> Self {
+            Self(self.0.this_month())
+        }
+
+        pub fn last_month(self) -> Self {
+            Self(self.0.last_month())
+        }
+
+        pub fn year_to_date(self) -> Self {
+            Self(self.0.year_to_date())
+        }
+
+        pub fn quarter(self, year: i32, quarter: u32) -> Self {
+            Self(self.0.quarter(year, quarter))
+        }
+    };
+}
+
+/// A typed query which determines the kind of reports it is sent for.
+pub trait ReportQuery: AsRef<Query> {
+    const KIND: ReportKind;
+}
+
+/// Implements conversions between a typed query and `Query`
+macro_rules! impl_typed_query {
+    ($name:ident, $kind:ident) => {
+        impl ReportQuery for $name {
+            const KIND: ReportKind = ReportKind::$kind;
+        }
+
+        impl AsRef<Query> for $name {
+            fn as_ref(&self) -> &Query {
+                &self.0
+            }
+        }
+
+        impl From<$name> for Query {
+            fn from(from: $name) -> Query {
+                from.0
+            }
+        }
+    };
+}
+
+/// A query of summary reports.
+///
+/// Only parameters which summary reports accept can be set.
+#[derive(Clone, Default, Debug)]
+pub struct SummaryQuery(Query);
+
+impl SummaryQuery {
+    define_common_setters!();
+    define_range_setters!();
+
+    /// Splits the query into windows of at most one year. See `Query::split_by_year()`.
+    pub fn split_by_year(&self) -> Vec<Self> {
+        self.0.split_by_year()
+            .into_iter()
+            .map(Self)
+            .collect()
+    }
+
+    /// Checks constraints of query parameters. See `Query::validate()`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.0.validate(ReportKind::Summary)
+    }
+
+    define_typed_setter!(grouping, SummaryGroupingKind, GroupingKind);
+    define_typed_setter!(subgrouping, GroupingKind);
+    define_typed_setter!(subgrouping_ids, bool);
+    define_typed_setter!(grouped_time_entry_ids, bool);
+    define_typed_setter!(order_field, SummaryOrderKind, OrderKind);
+}
+
+impl_typed_query!(SummaryQuery, Summary);
+
+/// A query of weekly reports.
+///
+/// Only parameters which weekly reports accept can be set.
+/// A weekly report always covers the 7 days from `since`, so `until` can't be set.
+#[derive(Clone, Default, Debug)]
+pub struct WeeklyQuery(Query);
+
+impl WeeklyQuery {
+    define_common_setters!();
+
+    /// Sets `since` to Monday of the current week in UTC. Use `Calendar` for other timezones or first weekdays.
+    pub fn this_week(self) -> Self {
+        self.since_date(Calendar::default().this_week().since)
+    }
+
+    /// Sets `since` to Monday of the previous week in UTC.
+    pub fn last_week(self) -> Self {
+        self.since_date(Calendar::default().last_week().since)
+    }
+
+    /// Checks constraints of query parameters. See `Query::validate()`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.0.validate(ReportKind::Weekly)
+    }
+
+    define_typed_setter!(grouping, WeeklyGroupingKind, GroupingKind);
+    define_typed_setter!(calculate, CalculateKind);
+    define_typed_setter!(order_field, WeeklyOrderKind, OrderKind);
+}
+
+impl_typed_query!(WeeklyQuery, Weekly);
+
+/// A query of detailed reports.
+///
+/// Only parameters which detailed reports accept can be set.
+#[derive(Clone, Default, Debug)]
+pub struct DetailedQuery(Query);
+
+impl DetailedQuery {
+    define_common_setters!();
+    define_range_setters!();
+
+    /// Splits the query into windows of at most one year. See `Query::split_by_year()`.
+    pub fn split_by_year(&self) -> Vec<Self> {
+        self.0.split_by_year()
+            .into_iter()
+            .map(Self)
+            .collect()
+    }
+
+    /// Checks constraints of query parameters. See `Query::validate()`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.0.validate(ReportKind::Detailed)
+    }
+
+    define_typed_setter!(page, u64);
+    define_typed_setter!(order_field, DetailedOrderKind, OrderKind);
+}
+
+impl_typed_query!(DetailedQuery, Detailed);
+
+/// Groupings accepted by summary reports
+#[derive(Clone, Debug)]
+pub enum SummaryGroupingKind {
+    Projects,
+    Clients,
+    Users,
+}
+
+impl From<SummaryGroupingKind> for GroupingKind {
+    fn from(from: SummaryGroupingKind) -> GroupingKind {
+        match from {
+            SummaryGroupingKind::Projects => GroupingKind::Projects,
+            SummaryGroupingKind::Clients => GroupingKind::Clients,
+            SummaryGroupingKind::Users => GroupingKind::Users,
+        }
+    }
+}
+
+/// Groupings accepted by weekly reports
+#[derive(Clone, Debug)]
+pub enum WeeklyGroupingKind {
+    Projects,
+    Users,
+}
+
+impl From<WeeklyGroupingKind> for GroupingKind {
+    fn from(from: WeeklyGroupingKind) -> GroupingKind {
+        match from {
+            WeeklyGroupingKind::Projects => GroupingKind::Projects,
+            WeeklyGroupingKind::Users => GroupingKind::Users,
+        }
+    }
+}
+
+/// Order fields accepted by summary reports
+#[derive(Clone, Debug)]
+pub enum SummaryOrderKind {
+    Title,
+    Duration,
+    Amount,
+}
+
+impl From<SummaryOrderKind> for OrderKind {
+    fn from(from: SummaryOrderKind) -> OrderKind {
+        match from {
+            SummaryOrderKind::Title => OrderKind::Title,
+            SummaryOrderKind::Duration => OrderKind::Duration,
+            SummaryOrderKind::Amount => OrderKind::Amount,
+        }
+    }
+}
+
+/// Order fields accepted by weekly reports
+#[derive(Clone, Debug)]
+pub enum WeeklyOrderKind {
+    Title,
+    Day1,
+    Day2,
+    Day3,
+    Day4,
+    Day5,
+    Day6,
+    Day7,
+    WeekTotal,
+}
+
+impl From<WeeklyOrderKind> for OrderKind {
+    fn from(from: WeeklyOrderKind) -> OrderKind {
+        match from {
+            WeeklyOrderKind::Title => OrderKind::Title,
+            WeeklyOrderKind::Day1 => OrderKind::Day1,
+            WeeklyOrderKind::Day2 => OrderKind::Day2,
+            WeeklyOrderKind::Day3 => OrderKind::Day3,
+            WeeklyOrderKind::Day4 => OrderKind::Day4,
+            WeeklyOrderKind::Day5 => OrderKind::Day5,
+            WeeklyOrderKind::Day6 => OrderKind::Day6,
+            WeeklyOrderKind::Day7 => OrderKind::Day7,
+            WeeklyOrderKind::WeekTotal => OrderKind::WeekTotal,
+        }
+    }
+}
+
+/// Order fields accepted by detailed reports
+#[derive(Clone, Debug)]
+pub enum DetailedOrderKind {
+    Date,
+    Description,
+    Duration,
+    User,
+}
+
+impl From<DetailedOrderKind> for OrderKind {
+    fn from(from: DetailedOrderKind) -> OrderKind {
+        match from {
+            DetailedOrderKind::Date => OrderKind::Date,
+            DetailedOrderKind::Description => OrderKind::Description,
+            DetailedOrderKind::Duration => OrderKind::Duration,
+            DetailedOrderKind::User => OrderKind::User,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +877,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn vec_special() {
         use chrono::{DateTime, Utc};
 
@@ -335,4 +896,232 @@ mod tests {
 
         assert_eq!(vec, ans);
     }
+
+    #[test]
+    fn since_in_timezone() {
+        use chrono::FixedOffset;
+
+        let dt = DateTime::parse_from_rfc3339("2021-02-28T19:59:59Z").unwrap();
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let query = Query::new(334)
+            .since_in(dt.with_timezone(&tokyo))
+            .until_in(dt.with_timezone(&Utc));
+
+        let ans = vec![
+            ("workspace_id", String::from("334")),
+            ("since", String::from("2021-03-01")),
+            ("until", String::from("2021-02-28")),
+        ];
+
+        assert_eq!(query.to_vec(), ans);
+    }
+
+    #[test]
+    fn typed_queries() {
+        let summary = SummaryQuery::new(334)
+            .grouping(SummaryGroupingKind::Clients)
+            .subgrouping(GroupingKind::Tasks)
+            .subgrouping_ids(true)
+            .order_field(SummaryOrderKind::Amount)
+            .rounding(true);
+        let ans = vec![
+            ("workspace_id", String::from("334")),
+            ("grouping", String::from("clients")),
+            ("subgrouping", String::from("tasks")),
+            ("subgrouping_ids", String::from("true")),
+            ("order_field", String::from("amount")),
+            ("rounding", String::from("on")),
+        ];
+        assert_eq!(summary.to_vec(), ans);
+
+        let weekly = WeeklyQuery::new(334)
+            .grouping(WeeklyGroupingKind::Users)
+            .calculate(CalculateKind::Time)
+            .order_field(WeeklyOrderKind::WeekTotal);
+        let ans = vec![
+            ("workspace_id", String::from("334")),
+            ("grouping", String::from("users")),
+            ("calculate", String::from("time")),
+            ("order_field", String::from("week_total")),
+        ];
+        assert_eq!(weekly.to_vec(), ans);
+
+        let detailed = DetailedQuery::new(334)
+            .page(2)
+            .user_ids(vec![2, 64])
+            .order_field(DetailedOrderKind::Date);
+        let ans = vec![
+            ("workspace_id", String::from("334")),
+            ("page", String::from("2")),
+            ("user_ids", String::from("2,64")),
+            ("order_field", String::from("date")),
+        ];
+        assert_eq!(detailed.to_vec(), ans);
+        assert_eq!(Query::from(detailed).to_vec(), ans);
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%F").unwrap()
+    }
+
+    #[test]
+    fn validate_basic() {
+        let query = Query::new(334)
+            .grouping(GroupingKind::Users)
+            .subgrouping(GroupingKind::Projects)
+            .order_field(OrderKind::Duration)
+            .since_date(date("2020-03-01"))
+            .until_date(date("2021-02-28"));
+
+        assert_eq!(query.validate(ReportKind::Summary), Ok(()));
+        assert_eq!(Query::new(334).validate(ReportKind::Weekly), Ok(()));
+        assert_eq!(Query::new(334).validate(ReportKind::Detailed), Ok(()));
+    }
+
+    #[test]
+    fn validate_dates() {
+        let query = Query::new(334)
+            .since_date(date("2021-03-01"))
+            .until_date(date("2021-02-28"));
+        let err = query.validate(ReportKind::Detailed).unwrap_err();
+        assert_eq!(err.problems, vec![QueryProblem::SinceAfterUntil]);
+
+        let query = Query::new(334)
+            .since_date(date("2020-02-28"))
+            .until_date(date("2021-03-01"));
+        let err = query.validate(ReportKind::Detailed).unwrap_err();
+        assert_eq!(err.problems, vec![QueryProblem::RangeTooLong]);
+    }
+
+    #[test]
+    fn validate_one_year_boundary() {
+        let query = DetailedQuery::new(334)
+            .since_date(date("2020-01-01"))
+            .until_date(date("2020-12-31"));
+        assert_eq!(query.validate(), Ok(()));
+        assert_eq!(query.split_by_year().len(), 1);
+
+        let query = DetailedQuery::new(334)
+            .since_date(date("2020-01-01"))
+            .until_date(date("2021-01-01"));
+        assert_eq!(query.validate().unwrap_err().problems, vec![QueryProblem::RangeTooLong]);
+        assert_eq!(query.split_by_year().len(), 2);
+        assert!(query.split_by_year().iter().all(|window| window.validate().is_ok()));
+    }
+
+    #[test]
+    fn validate_all_problems() {
+        let query = Query::new(334)
+            .grouping(GroupingKind::Clients)
+            .order_field(OrderKind::Date)
+            .since_date(date("2021-03-01"))
+            .until_date(date("2021-02-28"));
+        let err = query.validate(ReportKind::Weekly).unwrap_err();
+
+        assert_eq!(err, ValidationError {
+            report: ReportKind::Weekly,
+            problems: vec![
+                QueryProblem::SinceAfterUntil,
+                QueryProblem::InvalidGrouping(GroupingKind::Clients),
+                QueryProblem::InvalidOrderField(OrderKind::Date),
+            ],
+        });
+        assert_eq!(
+            err.to_string(),
+            "invalid weekly report query: since is later than until; grouping clients is not supported; order_field date is not supported"
+        );
+    }
+
+    #[test]
+    fn validate_summary_grouping() {
+        let query = SummaryQuery::new(334)
+            .grouping(SummaryGroupingKind::Users)
+            .subgrouping(GroupingKind::Users);
+        let err = query.validate().unwrap_err();
+        assert_eq!(err.problems, vec![QueryProblem::SameGrouping(GroupingKind::Users)]);
+
+        let query = SummaryQuery::new(334)
+            .subgrouping(GroupingKind::Projects);
+        let err = query.validate().unwrap_err();
+        assert_eq!(err.problems, vec![QueryProblem::SameGrouping(GroupingKind::Projects)]);
+
+        let query = Query::new(334).grouping(GroupingKind::Tasks);
+        let err = query.validate(ReportKind::Summary).unwrap_err();
+        assert_eq!(err.problems, vec![QueryProblem::InvalidGrouping(GroupingKind::Tasks)]);
+    }
+
+    #[test]
+    fn split_by_year_basic() {
+        let range = |query: &DetailedQuery| {
+            let vec = query.to_vec();
+            (vec[1].1.clone(), vec[2].1.clone())
+        };
+
+        let query = DetailedQuery::new(334)
+            .since_date(date("2019-03-15"))
+            .until_date(date("2021-06-30"));
+        let windows: Vec<_> = query.split_by_year().iter().map(range).collect();
+        assert_eq!(windows, vec![
+            (String::from("2019-03-15"), String::from("2020-03-14")),
+            (String::from("2020-03-15"), String::from("2021-03-14")),
+            (String::from("2021-03-15"), String::from("2021-06-30")),
+        ]);
+        assert!(query.split_by_year().iter().all(|window| window.validate().is_ok()));
+
+        let query = DetailedQuery::new(334)
+            .since_date(date("2020-01-01"))
+            .until_date(date("2020-12-31"));
+        assert_eq!(query.split_by_year().len(), 1);
+
+        let query = DetailedQuery::new(334)
+            .since_date(date("2020-01-01"))
+            .until_date(date("2021-01-01"));
+        assert_eq!(query.split_by_year().len(), 2);
+    }
+
+    #[test]
+    fn split_by_year_unbounded() {
+        assert_eq!(Query::new(334).split_by_year().len(), 1);
+
+        let query = Query::new(334)
+            .since_date(date("2021-03-01"))
+            .until_date(date("2021-02-28"));
+        assert_eq!(query.split_by_year().len(), 1);
+    }
+
+    #[test]
+    fn date_range_presets() {
+        let query = Query::new(334).quarter(2021, 1);
+        let ans = vec![
+            ("workspace_id", String::from("334")),
+            ("since", String::from("2021-01-01")),
+            ("until", String::from("2021-03-31")),
+        ];
+        assert_eq!(query.to_vec(), ans);
+
+        let range = Calendar::default().last_month();
+        let query = SummaryQuery::new(334).last_month();
+        let ans = vec![
+            ("workspace_id", String::from("334")),
+            ("since", range.since.format("%F").to_string()),
+            ("until", range.until.format("%F").to_string()),
+        ];
+        assert_eq!(query.to_vec(), ans);
+    }
+
+    #[test]
+    fn weekly_query_since_only() {
+        use chrono::{Datelike, Weekday};
+
+        let vec = WeeklyQuery::new(334).last_week().to_vec();
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec[1].0, "since");
+        assert_eq!(NaiveDate::parse_from_str(&vec[1].1, "%F").unwrap().weekday(), Weekday::Mon);
+    }
+
+    #[test]
+    #[should_panic(expected = "quarter must be in 1..=4")]
+    fn quarter_illegal() {
+        let _ = Query::new(334).quarter(2021, 5);
+    }
 }
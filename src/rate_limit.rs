@@ -0,0 +1,127 @@
+//! A token bucket to keep request rates under the limit of Toggl.
+//!
+//! Toggl asks clients of Reports API to send about one request per second per API token.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// A token bucket rate limiter.
+///
+/// Clones share the same bucket, so one limiter can throttle every `Toggl` handle
+/// which uses the same API token.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    per_second: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Takes a token if available, or returns how long to wait for the next token.
+    fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.per_second))
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Creates a limiter which allows `per_second` requests per second on average
+    /// and at most `burst` requests at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `per_second` is not positive or `burst` is zero.
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        assert!(per_second > 0.0, "per_second must be positive");
+        assert!(burst > 0, "burst must be positive");
+
+        let bucket = Bucket {
+            capacity: burst as f64,
+            tokens: burst as f64,
+            per_second,
+            updated_at: Instant::now(),
+        };
+
+        Self {
+            bucket: Arc::new(Mutex::new(bucket)),
+        }
+    }
+
+    /// Waits until a request is allowed to be sent.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = self.bucket
+                .lock()
+                .unwrap()
+                .try_take(Instant::now());
+
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    /// One request per second without bursts, as Toggl recommends.
+    fn default() -> Self {
+        Self::new(1.0, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_basic() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed().as_secs(), 0);
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed().as_secs(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_burst() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed().as_millis(), 0);
+        limiter.acquire().await;
+        assert_eq!(start.elapsed().as_millis(), 500);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_shared() {
+        let limiter = RateLimiter::default();
+        let cloned = limiter.clone();
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        cloned.acquire().await;
+        assert_eq!(start.elapsed().as_secs(), 1);
+    }
+}
@@ -1,7 +1,8 @@
 //! Data structures to store results of reports
 
 use chrono::Duration;
-use serde::{Deserialize, Deserializer};
+use crate::query::GroupingKind;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::convert::{AsRef, AsMut};
 use std::ops::{Deref, DerefMut};
 
@@ -19,6 +20,13 @@ fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
     )
 }
 
+/// Serializer for chrono::Duration, which writes milliseconds as `deserialize_duration` reads
+fn serialize_duration<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    serializer.serialize_i64(duration.num_milliseconds())
+}
+
 /// Deserializer for amount of money
 fn deserialize_earning<'de, D>(deserializer: D) -> Result<f64, D::Error>
     where D: Deserializer<'de>
@@ -36,10 +44,10 @@ fn deserialize_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
 }
 
 /// A data structure to wrap chrono::Duration to deserialize nullable json
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(transparent)]
 pub struct DurationWrapper(
-    #[serde(deserialize_with="deserialize_duration")]
+    #[serde(deserialize_with="deserialize_duration", serialize_with="serialize_duration")]
     Duration
 );
 
@@ -76,7 +84,7 @@ impl AsMut<Duration> for DurationWrapper {
 }
 
 /// A data structure to wrap chrono::Duration to deserialize nullable json
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(transparent)]
 pub struct EarningWrapper (
     #[serde(deserialize_with="deserialize_earning")]
@@ -116,21 +124,31 @@ impl AsMut<f64> for EarningWrapper {
 }
 
 /// A type to represent total earnings of a week
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct EarningTotals {
     pub currency: Option<String>,
     pub amount: [EarningWrapper; 8],
 }
 
 /// A structure to sotre currency
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Currency {
     pub currency: Option<String>,
     pub amount: EarningWrapper,
 }
 
+/// Adds amounts of `other` to `currencies`, matching by currency
+pub(crate) fn merge_currencies(currencies: &mut Vec<Currency>, other: Vec<Currency>) {
+    for currency in other {
+        match currencies.iter_mut().find(|cur| cur.currency == currency.currency) {
+            Some(cur) => *cur.amount += *currency.amount,
+            None => currencies.push(currency),
+        }
+    }
+}
+
 /// A generic structure to store response from Toggl
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Report<Data> {
     pub total_grand: DurationWrapper,
     pub total_billable: EarningWrapper,
@@ -139,7 +157,7 @@ pub struct Report<Data> {
 }
 
 /// A structure to represent Title entries
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Title {
     ProjectTitle (
@@ -163,6 +181,19 @@ pub enum Title {
 const NONE_STR: &str= "(none)";
 
 impl Title {
+    /// Deserializes the title of `kind` without guessing from its fields.
+    pub fn deserialize_as<'de, D>(deserializer: D, kind: &GroupingKind) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        match kind {
+            GroupingKind::Projects => ProjectTitle::deserialize(deserializer).map(Title::ProjectTitle),
+            GroupingKind::Clients => ClientTitle::deserialize(deserializer).map(Title::ClientTitle),
+            GroupingKind::Users => UserTitle::deserialize(deserializer).map(Title::UserTitle),
+            GroupingKind::Tasks => TaskTitle::deserialize(deserializer).map(Title::TaskTitle),
+            GroupingKind::TimeEntries => TimeEntryTitle::deserialize(deserializer).map(Title::TimeEntryTitle),
+        }
+    }
+
     /// Convert to String
     pub fn name(&self) -> String {
         match self {
@@ -176,7 +207,7 @@ impl Title {
 }
 
 /// A structure to represent title entries of projects
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectTitle {
     #[serde(deserialize_with="deserialize_option")]
     pub project: Option<String>,
@@ -185,14 +216,14 @@ pub struct ProjectTitle {
 }
 
 impl ProjectTitle {
-    fn name(&self) -> String {
+    pub fn name(&self) -> String {
         self.project.clone()
             .unwrap_or_else(|| NONE_STR.to_owned())
     }
 }
 
 /// A structure to represent title entries of clients
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ClientTitle {
     #[serde(deserialize_with="deserialize_option")]
     pub client: Option<String>,
@@ -206,7 +237,7 @@ impl ClientTitle {
 }
 
 /// A structure to represent title entries of users
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct UserTitle {
     #[serde(deserialize_with="deserialize_option")]
     pub user: Option<String>,
@@ -220,7 +251,7 @@ impl UserTitle {
 }
 
 /// A structure to represent title entries of tasks
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TaskTitle {
     #[serde(deserialize_with="deserialize_option")]
     pub task: Option<String>,
@@ -234,7 +265,7 @@ impl TaskTitle {
 }
 
 /// A structure to represent title entries of time entries
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TimeEntryTitle {
     #[serde(deserialize_with="deserialize_option")]
     pub time_entry: Option<String>,
@@ -248,10 +279,11 @@ impl TimeEntryTitle {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use super::*;
 
-    #[derive(Debug, Deserialize, Eq, PartialEq)]
+    #[derive(Debug, Deserialize, Serialize, Eq, PartialEq)]
     struct Hoge {
         duration: DurationWrapper
     }
@@ -274,6 +306,15 @@ mod tests {
         assert_eq!(ans, result);
     }
 
+    #[test]
+    fn duration_serialize() {
+        let hoge = Hoge { duration: DurationWrapper ( Duration::milliseconds(334) ) };
+        let json = serde_json::to_string(&hoge).unwrap();
+
+        assert_eq!(json, r#"{"duration":334}"#);
+        assert_eq!(serde_json::from_str::<Hoge>(&json).unwrap(), hoge);
+    }
+
     #[test]
     fn duration_deref() {
         let wrapper = DurationWrapper(Duration::milliseconds(334));
@@ -285,7 +326,7 @@ mod tests {
         assert_eq!(&ans, wrapper.as_ref());
     }
 
-    #[derive(Debug, Deserialize, PartialEq)]
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
     struct Fuga {
         earning: EarningWrapper,
     }
@@ -308,6 +349,15 @@ mod tests {
         assert_eq!(ans, result);
     }
 
+    #[test]
+    fn earning_serialize() {
+        let fuga = Fuga { earning: EarningWrapper(33.4) };
+        let json = serde_json::to_string(&fuga).unwrap();
+
+        assert_eq!(json, r#"{"earning":33.4}"#);
+        assert_eq!(serde_json::from_str::<Fuga>(&json).unwrap(), fuga);
+    }
+
     #[test]
     fn earning_deref() {
         let wrapper = EarningWrapper(334.0);
@@ -350,6 +400,21 @@ mod tests {
         let _: Poyo = serde_json::from_str(&json).unwrap();
     }
 
+    #[test]
+    fn merge_currencies_basic() {
+        let currency = |currency: Option<&str>, amount| Currency {
+            currency: currency.map(String::from),
+            amount: EarningWrapper(amount),
+        };
+        let mut currencies = vec![currency(Some("EUR"), 10.0), currency(None, 0.0)];
+        merge_currencies(&mut currencies, vec![currency(Some("USD"), 3.0), currency(Some("EUR"), 2.5)]);
+
+        let result: Vec<_> = currencies.iter()
+            .map(|cur| (cur.currency.as_deref(), *cur.amount))
+            .collect();
+        assert_eq!(result, vec![(Some("EUR"), 12.5), (None, 0.0), (Some("USD"), 3.0)]);
+    }
+
     #[test]
     fn title_name_basic() {
         let project = Title::ProjectTitle(ProjectTitle {
@@ -379,6 +444,34 @@ mod tests {
         assert_eq!(time_entry.name(), String::from("te"));
     }
 
+    #[test]
+    fn title_serialize() {
+        let json = serde_json::json!({"project": "proj", "client": null});
+        let title: Title = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&title).unwrap(), json);
+
+        let json = serde_json::json!({"user": "us"});
+        let title: Title = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&title).unwrap(), json);
+    }
+
+    #[test]
+    fn title_deserialize_as() {
+        let json = serde_json::json!({"user": "us", "project": null, "client": null});
+
+        let guessed: Title = serde_json::from_value(json.clone()).unwrap();
+        assert!(matches!(guessed, Title::ProjectTitle(_)));
+
+        let title = Title::deserialize_as(json, &GroupingKind::Users).unwrap();
+        assert!(matches!(title, Title::UserTitle(_)));
+        assert_eq!(title.name(), "us");
+
+        let json = serde_json::json!({"client": "cli"});
+        assert!(Title::deserialize_as(json, &GroupingKind::Projects).is_err());
+    }
+
     #[test]
     fn title_name_none() {
         let project = Title::ProjectTitle(ProjectTitle {
@@ -0,0 +1,241 @@
+//! A policy to retry requests failed by rate limits or temporary errors of Toggl.
+
+use crate::error::{Error, Result};
+use std::future::Future;
+use std::time::Duration;
+
+/// A policy to retry failed requests with exponential backoff.
+///
+/// Requests are retried on `429 Too Many Requests`, `5xx` and connection errors.
+/// Other errors, e.g. `403 Forbidden` by a wrong API token, are returned immediately.
+///
+/// ```
+/// use std::time::Duration;
+/// use toggl_reports::{RetryPolicy, Toggl};
+///
+/// let toggl = Toggl::builder("00000000000000000000000000000000")
+///     .retry_policy(RetryPolicy::new()
+///         .max_attempts(5)
+///         .base_delay(Duration::from_secs(1)))
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+    honor_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// Creates a policy of 3 attempts, starting from 500ms delay.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            honor_retry_after: true,
+        }
+    }
+
+    /// Sets the number of attempts including the first request.
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..self
+        }
+    }
+
+    /// Sets the delay before the first retry. The delay doubles on each retry.
+    pub fn base_delay(self, base_delay: Duration) -> Self {
+        Self {
+            base_delay,
+            ..self
+        }
+    }
+
+    /// Sets the upper bound of delays.
+    pub fn max_delay(self, max_delay: Duration) -> Self {
+        Self {
+            max_delay,
+            ..self
+        }
+    }
+
+    /// Sets the ratio of the delay which is randomly cut off, between 0.0 and 1.0.
+    pub fn jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Sets whether to wait as long as `Retry-After` of 429 responses.
+    pub fn honor_retry_after(self, honor_retry_after: bool) -> Self {
+        Self {
+            honor_retry_after,
+            ..self
+        }
+    }
+
+    /// Returns the delay before the next attempt, or `None` to give up.
+    ///
+    /// `attempt` is the number of attempts already made.
+    pub(crate) fn next_delay(&self, attempt: u32, err: &Error) -> Option<Duration> {
+        if attempt >= self.max_attempts || !is_retryable(err) {
+            return None;
+        }
+
+        if let Error::RateLimited { retry_after: Some(retry_after) } = err {
+            if self.honor_retry_after {
+                return Some(*retry_after);
+            }
+        }
+
+        let backoff = self.base_delay
+            .checked_mul(1 << attempt.saturating_sub(1).min(16))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        Some(backoff.mul_f64(1.0 - self.jitter * fastrand::f64()))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Calls `f` until it succeeds or `policy` gives up, waiting between attempts with `sleep`.
+///
+/// Both the async and the blocking clients retry through this loop.
+pub(crate) async fn retry<T, F, Fut, S, SFut>(policy: Option<&RetryPolicy>, mut f: F, mut sleep: S) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+    S: FnMut(Duration) -> SFut,
+    SFut: Future<Output = ()>,
+{
+    let mut attempt = 1;
+    loop {
+        let err = match f().await {
+            Ok(res) => return Ok(res),
+            Err(err) => err,
+        };
+
+        match policy.and_then(|policy| policy.next_delay(attempt, &err)) {
+            Some(delay) => sleep(delay).await,
+            None => return Err(err),
+        }
+        attempt += 1;
+    }
+}
+
+/// Returns whether `err` may be resolved by retrying.
+fn is_retryable(err: &Error) -> bool {
+    match err {
+        Error::Transport(err) => err.is_timeout() || err.is_connect(),
+        // Custom transports can't tell whether their failures are transient
+        Error::Custom(_) => false,
+        Error::RateLimited { .. } => true,
+        Error::Api { status, .. } | Error::Status { status, .. } => status.is_server_error(),
+        Error::Invalid(_) | Error::Decode { .. } | Error::Io(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn status(status: StatusCode) -> Error {
+        Error::Status { status, body: String::new() }
+    }
+
+    #[test]
+    fn next_delay_backoff() {
+        let policy = RetryPolicy::new()
+            .max_attempts(5)
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5))
+            .jitter(0.0);
+        let err = status(StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(policy.next_delay(1, &err), Some(Duration::from_secs(1)));
+        assert_eq!(policy.next_delay(2, &err), Some(Duration::from_secs(2)));
+        assert_eq!(policy.next_delay(3, &err), Some(Duration::from_secs(4)));
+        assert_eq!(policy.next_delay(4, &err), Some(Duration::from_secs(5)));
+        assert_eq!(policy.next_delay(5, &err), None);
+    }
+
+    #[test]
+    fn next_delay_jitter() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_secs(1))
+            .jitter(0.5);
+        let err = status(StatusCode::BAD_GATEWAY);
+
+        for _ in 0..100 {
+            let delay = policy.next_delay(1, &err).unwrap();
+            assert!(Duration::from_millis(500) <= delay && delay <= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn next_delay_retry_after() {
+        let policy = RetryPolicy::new().jitter(0.0);
+        let err = Error::RateLimited { retry_after: Some(Duration::from_secs(3)) };
+
+        assert_eq!(policy.next_delay(1, &err), Some(Duration::from_secs(3)));
+
+        let policy = policy.honor_retry_after(false);
+        assert_eq!(policy.next_delay(1, &err), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn next_delay_give_up() {
+        let policy = RetryPolicy::new();
+
+        assert_eq!(policy.next_delay(1, &status(StatusCode::FORBIDDEN)), None);
+        assert_eq!(policy.next_delay(1, &status(StatusCode::UNAUTHORIZED)), None);
+        assert_eq!(policy.next_delay(1, &status(StatusCode::BAD_REQUEST)), None);
+        assert_eq!(policy.next_delay(1, &Error::custom("connection reset")), None);
+    }
+
+    #[test]
+    fn retry_until_success() {
+        let policy = RetryPolicy::new().max_attempts(3).base_delay(Duration::from_secs(1)).jitter(0.0);
+        let mut results = vec![Ok(334), Err(status(StatusCode::BAD_GATEWAY)), Err(status(StatusCode::SERVICE_UNAVAILABLE))];
+        let mut delays = vec![];
+
+        let res = futures::executor::block_on(retry(
+            Some(&policy),
+            || futures::future::ready(results.pop().unwrap()),
+            |delay| {
+                delays.push(delay);
+                futures::future::ready(())
+            },
+        ));
+
+        assert_eq!(res.unwrap(), 334);
+        assert_eq!(delays, vec![Duration::from_secs(1), Duration::from_secs(2)]);
+    }
+
+    #[test]
+    fn retry_without_policy() {
+        let mut calls = 0;
+        let res: Result<()> = futures::executor::block_on(retry(
+            None,
+            || {
+                calls += 1;
+                futures::future::ready(Err(status(StatusCode::SERVICE_UNAVAILABLE)))
+            },
+            |_| futures::future::ready(()),
+        ));
+
+        assert!(res.is_err());
+        assert_eq!(calls, 1);
+    }
+}
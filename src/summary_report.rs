@@ -3,14 +3,122 @@
 //! For more information, see https://github.com/toggl/toggl_api_docs/blob/master/reports/summary.md.
 
 use crate::reports::{
-    EarningWrapper, Currency, DurationWrapper, Report, Title
+    self, EarningWrapper, Currency, DurationWrapper, Report, Title
 };
-use serde::Deserialize;
+use crate::query::{GroupingKind, Query};
+use serde::de::{DeserializeSeed, Error};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// A structure to store SummaryReports.
 pub type SummaryReport = Report<SummaryData>;
 
-#[derive(Debug, Deserialize)]
+impl SummaryReport {
+    /// Deserializes `json` with titles of the kinds which `query` requests.
+    ///
+    /// Unlike `serde_json::from_str()`, which picks the first title type that fits,
+    /// title types are chosen from `grouping` (projects by default) and `subgrouping`
+    /// (time entries by default) of `query`.
+    pub fn from_json(json: &str, query: &Query) -> serde_json::Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let report = SummaryReportSeed(query).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+
+        Ok(report)
+    }
+
+    /// Adds up totals, groups and items of `other`, e.g. to combine reports of different date ranges.
+    ///
+    /// Groups are matched by their id and title, and items by their title and currency.
+    pub fn merge(&mut self, other: SummaryReport) {
+        *self.total_grand += *other.total_grand;
+        *self.total_billable += *other.total_billable;
+        reports::merge_currencies(&mut self.total_currencies, other.total_currencies);
+
+        for data in other.data {
+            let found = self.data
+                .iter_mut()
+                .find(|elem| elem.id == data.id && elem.title.name() == data.title.name());
+            match found {
+                Some(elem) => elem.merge(data),
+                None => self.data.push(data),
+            }
+        }
+    }
+}
+
+/// A seed to deserialize `SummaryReport` with titles of the kinds which a query requests.
+pub(crate) struct SummaryReportSeed<'a>(pub(crate) &'a Query);
+
+impl<'de> DeserializeSeed<'de> for SummaryReportSeed<'_> {
+    type Value = SummaryReport;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where D: Deserializer<'de>
+    {
+        let grouping = self.0.grouping.as_ref().unwrap_or(&GroupingKind::Projects);
+        let subgrouping = self.0.subgrouping.as_ref().unwrap_or(&GroupingKind::TimeEntries);
+        let title = |value, kind, path: String| {
+            Title::deserialize_as(value, kind)
+                .map_err(|err| D::Error::custom(format!("{}: {}", path, err)))
+        };
+
+        let raw = Report::<RawSummaryData>::deserialize(deserializer)?;
+        let data = raw.data
+            .into_iter()
+            .enumerate()
+            .map(|(i, data)| {
+                let items = data.items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(j, item)| Ok(SummaryItem {
+                        title: title(item.title, subgrouping, format!("data[{}].items[{}].title", i, j))?,
+                        time: item.time,
+                        cur: item.cur,
+                        sum: item.sum,
+                        rate: item.rate,
+                    }))
+                    .collect::<Result<_, D::Error>>()?;
+
+                Ok(SummaryData {
+                    id: data.id,
+                    title: title(data.title, grouping, format!("data[{}].title", i))?,
+                    time: data.time,
+                    total_currencies: data.total_currencies,
+                    items,
+                })
+            })
+            .collect::<Result<_, D::Error>>()?;
+
+        Ok(Report {
+            total_grand: raw.total_grand,
+            total_billable: raw.total_billable,
+            total_currencies: raw.total_currencies,
+            data,
+        })
+    }
+}
+
+/// `SummaryData` whose titles are not deserialized yet
+#[derive(Deserialize)]
+struct RawSummaryData {
+    id: Option<u64>,
+    title: serde_json::Value,
+    time: DurationWrapper,
+    total_currencies: Vec<Currency>,
+    items: Vec<RawSummaryItem>,
+}
+
+/// `SummaryItem` whose title is not deserialized yet
+#[derive(Deserialize)]
+struct RawSummaryItem {
+    title: serde_json::Value,
+    time: DurationWrapper,
+    cur: Option<String>,
+    sum: EarningWrapper,
+    rate: EarningWrapper,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SummaryData {
     pub id: Option<u64>,
     pub title: Title,
@@ -19,11 +127,216 @@ pub struct SummaryData {
     pub items: Vec<SummaryItem>
 }
 
-#[derive(Debug, Deserialize)]
+impl SummaryData {
+    fn merge(&mut self, other: SummaryData) {
+        *self.time += *other.time;
+        reports::merge_currencies(&mut self.total_currencies, other.total_currencies);
+
+        for item in other.items {
+            let found = self.items
+                .iter_mut()
+                .find(|elem| elem.title.name() == item.title.name() && elem.cur == item.cur);
+            match found {
+                Some(elem) => {
+                    *elem.time += *item.time;
+                    *elem.sum += *item.sum;
+                }
+                None => self.items.push(item),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SummaryItem {
     pub title: Title,
     pub time: DurationWrapper,
     pub cur: Option<String>,
     pub sum: EarningWrapper,
     pub rate: EarningWrapper,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that every group and item title of a fixture decodes as the expected variant
+    macro_rules! fixture_test {
+        ($name:ident, $file:literal, $group:pat, $item:pat) => {
+            #[test]
+            fn $name() {
+                let json = include_str!(concat!("../tests/fixtures/summary/", $file, ".json"));
+                let report: SummaryReport = serde_json::from_str(json).unwrap();
+
+                assert!(!report.data.is_empty());
+                for data in &report.data {
+                    assert!(matches!(data.title, $group), "group: {:?}", data.title);
+                    assert!(!data.items.is_empty());
+                    for item in &data.items {
+                        assert!(matches!(item.title, $item), "item: {:?}", item.title);
+                    }
+                }
+            }
+        };
+    }
+
+    fixture_test!(projects_time_entries, "projects_time_entries", Title::ProjectTitle(_), Title::TimeEntryTitle(_));
+    fixture_test!(projects_tasks, "projects_tasks", Title::ProjectTitle(_), Title::TaskTitle(_));
+    fixture_test!(projects_users, "projects_users", Title::ProjectTitle(_), Title::UserTitle(_));
+    fixture_test!(clients_time_entries, "clients_time_entries", Title::ClientTitle(_), Title::TimeEntryTitle(_));
+    fixture_test!(clients_tasks, "clients_tasks", Title::ClientTitle(_), Title::TaskTitle(_));
+    fixture_test!(clients_projects, "clients_projects", Title::ClientTitle(_), Title::ProjectTitle(_));
+    fixture_test!(clients_users, "clients_users", Title::ClientTitle(_), Title::UserTitle(_));
+    fixture_test!(users_time_entries, "users_time_entries", Title::UserTitle(_), Title::TimeEntryTitle(_));
+    fixture_test!(users_tasks, "users_tasks", Title::UserTitle(_), Title::TaskTitle(_));
+    fixture_test!(users_projects, "users_projects", Title::UserTitle(_), Title::ProjectTitle(_));
+    fixture_test!(users_clients, "users_clients", Title::UserTitle(_), Title::ClientTitle(_));
+
+    /// Asserts that a fixture decodes with a query as the expected title variants
+    macro_rules! query_fixture_test {
+        ($name:ident, $file:literal, $grouping:ident => $group:ident, $subgrouping:ident => $item:ident) => {
+            #[test]
+            fn $name() {
+                let json = include_str!(concat!("../tests/fixtures/summary/", $file, ".json"));
+                let query = Query::new(334)
+                    .grouping(GroupingKind::$grouping)
+                    .subgrouping(GroupingKind::$subgrouping);
+                let report = SummaryReport::from_json(json, &query).unwrap();
+
+                assert!(!report.data.is_empty());
+                for data in &report.data {
+                    assert!(matches!(data.title, Title::$group(_)), "group: {:?}", data.title);
+                    for item in &data.items {
+                        assert!(matches!(item.title, Title::$item(_)), "item: {:?}", item.title);
+                    }
+                }
+            }
+        };
+    }
+
+    query_fixture_test!(query_projects_time_entries, "projects_time_entries", Projects => ProjectTitle, TimeEntries => TimeEntryTitle);
+    query_fixture_test!(query_projects_tasks, "projects_tasks", Projects => ProjectTitle, Tasks => TaskTitle);
+    query_fixture_test!(query_projects_users, "projects_users", Projects => ProjectTitle, Users => UserTitle);
+    query_fixture_test!(query_clients_time_entries, "clients_time_entries", Clients => ClientTitle, TimeEntries => TimeEntryTitle);
+    query_fixture_test!(query_clients_tasks, "clients_tasks", Clients => ClientTitle, Tasks => TaskTitle);
+    query_fixture_test!(query_clients_projects, "clients_projects", Clients => ClientTitle, Projects => ProjectTitle);
+    query_fixture_test!(query_clients_users, "clients_users", Clients => ClientTitle, Users => UserTitle);
+    query_fixture_test!(query_users_time_entries, "users_time_entries", Users => UserTitle, TimeEntries => TimeEntryTitle);
+    query_fixture_test!(query_users_tasks, "users_tasks", Users => UserTitle, Tasks => TaskTitle);
+    query_fixture_test!(query_users_projects, "users_projects", Users => UserTitle, Projects => ProjectTitle);
+    query_fixture_test!(query_users_clients, "users_clients", Users => UserTitle, Clients => ClientTitle);
+
+    #[test]
+    fn from_json_ambiguous_title() {
+        let json = serde_json::json!({
+            "total_grand": 3600000,
+            "total_billable": null,
+            "total_currencies": [],
+            "data": [{
+                "id": 1,
+                "title": {"user": "John Swift", "project": null, "client": null},
+                "time": 3600000,
+                "total_currencies": [],
+                "items": []
+            }]
+        }).to_string();
+
+        let guessed: SummaryReport = serde_json::from_str(&json).unwrap();
+        assert!(matches!(guessed.data[0].title, Title::ProjectTitle(_)));
+
+        let query = Query::new(334).grouping(GroupingKind::Users);
+        let report = SummaryReport::from_json(&json, &query).unwrap();
+        assert!(matches!(report.data[0].title, Title::UserTitle(_)));
+        assert_eq!(report.data[0].title.name(), "John Swift");
+    }
+
+    #[test]
+    fn from_json_mismatch() {
+        let json = include_str!("../tests/fixtures/summary/users_clients.json");
+        let query = Query::new(334).grouping(GroupingKind::Users);
+        let err = SummaryReport::from_json(json, &query).unwrap_err();
+
+        assert!(err.to_string().starts_with("data[0].items[0].title: "), "{}", err);
+    }
+
+    #[test]
+    fn merge_basic() {
+        let json = include_str!("../tests/fixtures/summary/projects_users.json");
+        let mut report: SummaryReport = serde_json::from_str(json).unwrap();
+        let other: SummaryReport = serde_json::from_value(serde_json::json!({
+            "total_grand": 3600000,
+            "total_billable": 3600000,
+            "total_currencies": [{"currency": "EUR", "amount": 10.0}],
+            "data": [{
+                "id": 1000,
+                "title": {"project": "Toggl Development", "client": "Toggl"},
+                "time": 3600000,
+                "total_currencies": [{"currency": "EUR", "amount": 10.0}],
+                "items": [
+                    {"title": {"user": "John Swift"}, "time": 1800000, "cur": "EUR", "sum": 5.0, "rate": 10.0},
+                    {"title": {"user": "Alice"}, "time": 1800000, "cur": "EUR", "sum": 5.0, "rate": 10.0}
+                ]
+            }, {
+                "id": 1002,
+                "title": {"project": "Marketing", "client": null},
+                "time": 0,
+                "total_currencies": [],
+                "items": []
+            }]
+        })).unwrap();
+        report.merge(other);
+
+        assert_eq!(report.total_grand.num_hours(), 7);
+        assert_eq!(*report.total_billable, 10800000.0);
+        assert_eq!(*report.total_currencies[0].amount, 30.0);
+        assert_eq!(report.data.len(), 3);
+        assert_eq!(report.data[0].time.num_minutes(), 240);
+        assert_eq!(*report.data[0].total_currencies[0].amount, 20.0);
+        assert_eq!(report.data[0].items.len(), 3);
+        assert_eq!(report.data[0].items[0].time.num_minutes(), 90);
+        assert_eq!(*report.data[0].items[0].sum, 15.0);
+        assert_eq!(report.data[0].items[2].title.name(), "Alice");
+        assert_eq!(report.data[2].title.name(), "Marketing");
+    }
+
+    #[test]
+    fn fixture_values() {
+        let json = include_str!("../tests/fixtures/summary/projects_users.json");
+        let report: SummaryReport = serde_json::from_str(json).unwrap();
+
+        assert_eq!(report.total_grand.num_hours(), 6);
+        assert_eq!(*report.total_billable, 7200000.0);
+        assert_eq!(report.data[0].id, Some(1000));
+        assert_eq!(report.data[0].title.name(), "Toggl Development");
+        assert_eq!(report.data[1].id, None);
+        assert_eq!(report.data[1].title.name(), "(none)");
+        assert_eq!(report.data[0].items[1].title.name(), "Jane Doe");
+        assert_eq!(report.data[0].items[1].time.num_hours(), 2);
+        assert_eq!(*report.data[0].items[1].sum, 0.0);
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let json = serde_json::json!({
+            "total_grand": 36004000,
+            "total_billable": 14400000.0,
+            "total_currencies": [{"currency": "EUR", "amount": 40.0}],
+            "data": [{
+                "id": 193009951,
+                "title": {"project": "Toggl Development", "client": "Toggl"},
+                "time": 14400000,
+                "total_currencies": [{"currency": "EUR", "amount": 40.0}],
+                "items": [{
+                    "title": {"time_entry": "Hard work"},
+                    "time": 14400000,
+                    "cur": "EUR",
+                    "sum": 40.0,
+                    "rate": 10.0
+                }]
+            }]
+        });
+        let report: SummaryReport = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&report).unwrap(), json);
+    }
+}
@@ -0,0 +1,315 @@
+//! Record-and-replay transports for offline tests.
+//!
+//! This module is enabled by the `testing` feature. `RecordingTransport` saves responses of
+//! the report endpoints to a JSON cassette file, and `ReplayTransport` serves them without
+//! network access. Interactions are keyed by the endpoint name (e.g. `summary`) and the
+//! parameters of `Query::to_vec()`. API tokens are never written to cassettes.
+//!
+//! Bodies which are not UTF-8, such as downloaded PDF or XLSX files, are stored in base64.
+//!
+//! ```no_run
+//! use toggl_reports::testing::{RecordingTransport, ReplayTransport};
+//! use toggl_reports::transport::ReqwestTransport;
+//! use toggl_reports::{query::SummaryQuery, Toggl};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! // Record once with a real API token...
+//! let recorder = RecordingTransport::new(ReqwestTransport::default(), "tests/cassettes/summary.json");
+//! let toggl = Toggl::builder("00000000000000000000000000000000")
+//!     .transport(recorder.clone())
+//!     .build();
+//! toggl.get_summary_report(&SummaryQuery::new(264)).await?;
+//! recorder.save()?;
+//!
+//! // ...and replay it in CI.
+//! let toggl = Toggl::builder("dummy")
+//!     .transport(ReplayTransport::from_file("tests/cassettes/summary.json")?)
+//!     .build();
+//! let report = toggl.get_summary_report(&SummaryQuery::new(264)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::transport::{BasicAuth, Response, Transport};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Query parameters excluded from keys of interactions
+const IGNORED_PARAMS: &[&str] = &["user_agent"];
+
+/// A string to replace API tokens with
+const REDACTED: &str = "<redacted>";
+
+/// A set of recorded interactions.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Reads a cassette from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes the cassette to a JSON file, creating parent directories if needed.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, json)
+    }
+
+    fn find(&self, endpoint: &str, query: &[(String, String)]) -> Option<&Interaction> {
+        self.interactions
+            .iter()
+            .find(|interaction| interaction.endpoint == endpoint && interaction.query == query)
+    }
+}
+
+/// A pair of a request and its response.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Interaction {
+    /// The endpoint name, e.g. `summary`
+    pub endpoint: String,
+    /// Query parameters except `user_agent`
+    pub query: Vec<(String, String)>,
+    pub status: u16,
+    /// The response body, which is encoded in base64 if `base64` is true
+    pub body: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub base64: bool,
+}
+
+impl Interaction {
+    /// Returns the raw response body, decoding base64 if needed.
+    pub fn body_bytes(&self) -> Result<Vec<u8>> {
+        if !self.base64 {
+            return Ok(self.body.clone().into_bytes());
+        }
+
+        BASE64.decode(&self.body).map_err(Error::custom)
+    }
+}
+
+/// Extracts the key of an interaction from a request.
+fn key(url: &str, query: &[(&'static str, String)]) -> (String, Vec<(String, String)>) {
+    let endpoint = url
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let query = query
+        .iter()
+        .filter(|(name, _)| !IGNORED_PARAMS.contains(name))
+        .map(|(name, val)| ((*name).to_owned(), val.clone()))
+        .collect();
+
+    (endpoint, query)
+}
+
+/// A `Transport` which records responses of another transport.
+///
+/// Clones share recorded interactions, so keep a clone to call `save()` after
+/// passing one to `TogglBuilder::transport()`.
+#[derive(Clone, Debug)]
+pub struct RecordingTransport<T> {
+    inner: Arc<T>,
+    path: PathBuf,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new<P: Into<PathBuf>>(inner: T, path: P) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            path: path.into(),
+            cassette: Arc::default(),
+        }
+    }
+
+    /// Returns interactions recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap().clone()
+    }
+
+    /// Writes recorded interactions to the cassette file.
+    pub fn save(&self) -> io::Result<()> {
+        self.cassette().save(&self.path)
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RecordingTransport<T> {
+    async fn get(&self, url: &str, query: &[(&'static str, String)], auth: &BasicAuth) -> Result<Response> {
+        let res = self.inner.get(url, query, auth).await?;
+
+        let redact = |val: &str| match auth.username.as_str() {
+            "" => val.to_owned(),
+            token => val.replace(token, REDACTED),
+        };
+        let (endpoint, query) = key(url, query);
+        let (body, base64) = match std::str::from_utf8(&res.body) {
+            Ok(body) => (redact(body), false),
+            Err(_) => (BASE64.encode(&res.body), true),
+        };
+        let interaction = Interaction {
+            endpoint,
+            query: query.into_iter().map(|(name, val)| (name, redact(&val))).collect(),
+            status: res.status.as_u16(),
+            body,
+            base64,
+        };
+
+        let mut cassette = self.cassette.lock().unwrap();
+        cassette.interactions.retain(|recorded| {
+            recorded.endpoint != interaction.endpoint || recorded.query != interaction.query
+        });
+        cassette.interactions.push(interaction);
+
+        Ok(res)
+    }
+}
+
+/// A `Transport` which serves responses from a cassette without network access.
+#[derive(Clone, Debug)]
+pub struct ReplayTransport {
+    cassette: Cassette,
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> Self {
+        Self { cassette }
+    }
+
+    /// Reads a cassette file saved by `RecordingTransport`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Cassette::load(path).map(Self::new)
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    /// Returns `Error::Custom` if the cassette has no interaction for the request.
+    async fn get(&self, url: &str, query: &[(&'static str, String)], _auth: &BasicAuth) -> Result<Response> {
+        let (endpoint, query) = key(url, query);
+        let interaction = self.cassette
+            .find(&endpoint, &query)
+            .ok_or_else(|| Error::custom(format!("no interaction recorded for {} {:?}", endpoint, query)))?;
+        let status = StatusCode::from_u16(interaction.status)
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        Ok(Response::new(status, interaction.body_bytes()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::SummaryQuery;
+    use crate::transport::ReqwestTransport;
+    use crate::Toggl;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const API_TOKEN: &str = "0123456789abcdef0123456789abcdef";
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_grand": 334000,
+                "total_billable": null,
+                "total_currencies": [],
+                "data": [],
+                "echo": API_TOKEN
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let path = std::env::temp_dir()
+            .join(format!("toggl-reports-{}", std::process::id()))
+            .join("summary.json");
+        let recorder = RecordingTransport::new(ReqwestTransport::default(), &path);
+        let toggl = Toggl::builder(API_TOKEN)
+            .base_url(&server.uri())
+            .transport(recorder.clone())
+            .build();
+        toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap();
+        recorder.save().unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains(API_TOKEN));
+        assert_eq!(recorder.cassette().interactions[0].endpoint, "summary");
+        assert_eq!(recorder.cassette().interactions[0].query, vec![(String::from("workspace_id"), String::from("334"))]);
+
+        let toggl = Toggl::builder("dummy")
+            .user_agent("another-agent")
+            .transport(ReplayTransport::from_file(&path).unwrap())
+            .build();
+        let report = toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap();
+        assert_eq!(report.total_grand.num_seconds(), 334);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replay_missing() {
+        let toggl = Toggl::builder("dummy")
+            .transport(ReplayTransport::new(Cassette::default()))
+            .build();
+        let err = toggl.get_summary_report(&SummaryQuery::new(334)).await.unwrap_err();
+
+        assert!(matches!(err, Error::Custom(_)));
+        assert!(err.to_string().contains("no interaction recorded for summary"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn record_binary() {
+        use crate::query::FileFormat;
+
+        let pdf = b"%PDF-1.4\n\xe2\x28\xa1".to_vec();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/summary.pdf"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(pdf.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let recorder = RecordingTransport::new(ReqwestTransport::default(), "unused.json");
+        let toggl = Toggl::builder(API_TOKEN)
+            .base_url(&server.uri())
+            .transport(recorder.clone())
+            .build();
+        let query = SummaryQuery::new(334);
+        toggl.get_report_file(FileFormat::Pdf, &query).await.unwrap();
+
+        let cassette = recorder.cassette();
+        assert!(cassette.interactions[0].base64);
+        assert_eq!(cassette.interactions[0].body_bytes().unwrap(), pdf);
+
+        let toggl = Toggl::builder("dummy")
+            .transport(ReplayTransport::new(cassette))
+            .build();
+        assert_eq!(toggl.get_report_file(FileFormat::Pdf, &query).await.unwrap(), pdf);
+    }
+}
@@ -0,0 +1,164 @@
+//! An abstraction of HTTP calls to Toggl.
+//!
+//! `Toggl` sends every request through a `Transport`. `ReqwestTransport` is used by default,
+//! and other implementations can be plugged in with `TogglBuilder::transport()`,
+//! e.g. to instrument requests or to fake responses in tests.
+
+use crate::Result;
+use async_trait::async_trait;
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, StatusCode};
+use std::fmt;
+use std::pin::Pin;
+
+/// Credentials of HTTP basic authentication.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BasicAuth {
+    pub username: String,
+    pub password: Option<String>,
+}
+
+impl BasicAuth {
+    /// Returns credentials for an API token of Toggl.
+    pub fn api_token(api_token: &str) -> Self {
+        Self {
+            username: api_token.to_owned(),
+            password: Some(String::from("api_token")),
+        }
+    }
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("username", &"<redacted>")
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// A response returned by `Transport`.
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Creates a response without headers.
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// A body received in chunks
+pub type BodyStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>;
+
+/// A response whose body is received in chunks.
+pub struct StreamResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: BodyStream,
+}
+
+impl fmt::Debug for StreamResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<Response> for StreamResponse {
+    fn from(res: Response) -> Self {
+        let body = res.body;
+        Self {
+            status: res.status,
+            headers: res.headers,
+            body: Box::pin(stream::once(async { Ok(body) })),
+        }
+    }
+}
+
+/// A trait to send GET requests.
+///
+/// Implementations should return any response as `Ok`, including non-success status codes,
+/// because `Toggl` decides how to handle them. Failures without a response can be returned
+/// with `Error::custom()`.
+#[async_trait]
+pub trait Transport: fmt::Debug + Send + Sync {
+    async fn get(&self, url: &str, query: &[(&'static str, String)], auth: &BasicAuth) -> Result<Response>;
+
+    /// Sends a GET request like `get()`, but returns the body as a stream of chunks.
+    ///
+    /// It is used to download report files. The default implementation receives the whole body with `get()`.
+    async fn get_stream(&self, url: &str, query: &[(&'static str, String)], auth: &BasicAuth) -> Result<StreamResponse> {
+        self.get(url, query, auth).await.map(StreamResponse::from)
+    }
+}
+
+/// The default `Transport` using `reqwest::Client`.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn get(&self, url: &str, query: &[(&'static str, String)], auth: &BasicAuth) -> Result<Response> {
+        let res = self.client
+            .get(url)
+            .query(query)
+            .basic_auth(&auth.username, auth.password.as_ref())
+            .send().await?;
+
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes().await?.to_vec();
+
+        Ok(Response { status, headers, body })
+    }
+
+    async fn get_stream(&self, url: &str, query: &[(&'static str, String)], auth: &BasicAuth) -> Result<StreamResponse> {
+        let res = self.client
+            .get(url)
+            .query(query)
+            .basic_auth(&auth.username, auth.password.as_ref())
+            .send().await?;
+
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes_stream()
+            .map(|chunk| chunk.map(|chunk| chunk.to_vec()).map_err(Into::into));
+
+        Ok(StreamResponse { status, headers, body: Box::pin(body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_auth_redacted() {
+        let auth = BasicAuth::api_token("00000000000000000000000000000000");
+        let debug = format!("{:?}", auth);
+
+        assert_eq!(auth.password.as_deref(), Some("api_token"));
+        assert!(!debug.contains("00000000000000000000000000000000"));
+        assert!(!debug.contains("api_token"));
+    }
+}
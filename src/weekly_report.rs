@@ -2,14 +2,18 @@
 //!
 //! For more information, see https://github.com/toggl/toggl_api_docs/blob/master/reports/weekly.md.
 
+use crate::date_range::Calendar;
 use crate::reports::{
     EarningTotals, TimeTotals, Report,
     ProjectTitle, UserTitle,
 };
-use serde::Deserialize;
+use crate::query::{CalculateKind, GroupingKind, Query, WeeklyQuery};
+use chrono::{Duration, NaiveDate};
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// A data structure to store WeeklyReports.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum WeeklyReport {
     ProjectTimeWeeklyReport (
@@ -26,7 +30,141 @@ pub enum WeeklyReport {
     )
 }
 
-#[derive(Debug, Deserialize)]
+impl WeeklyReport {
+    /// Deserializes `json` as the variant which `query` requests.
+    ///
+    /// Unlike `serde_json::from_str()`, which picks the first variant that fits,
+    /// the variant is chosen from `grouping` (projects by default) and `calculate`
+    /// (time by default) of `query`.
+    pub fn from_json(json: &str, query: &Query) -> serde_json::Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let report = WeeklyReportSeed(query).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+
+        Ok(report)
+    }
+
+    /// Labels totals of each project or user with the calendar dates of the week.
+    ///
+    /// The week starts from `since` of `query`, which the report was fetched with.
+    ///
+    /// If `since` is not set, the default of the API, six days before today, is assumed.
+    /// Today is taken in UTC when `pivot()` is called, so the dates may be off by a day
+    /// from the week the API returned, e.g. around midnight or in other timezones of the
+    /// workspace. Set `since` to get exact dates.
+    pub fn pivot(&self, query: &WeeklyQuery) -> WeeklyPivot {
+        self.pivot_at(query, Calendar::default().today())
+    }
+
+    /// Labels totals like `pivot()`, assuming the week ends at `today` if `since` is not set.
+    fn pivot_at(&self, query: &WeeklyQuery, today: NaiveDate) -> WeeklyPivot {
+        let since = query.as_ref().since
+            .unwrap_or_else(|| today - Duration::days(6));
+        let dates = (0..7)
+            .map(|day| since + Duration::days(day))
+            .collect();
+        let rows = match self {
+            WeeklyReport::ProjectTimeWeeklyReport(report) => report.data
+                .iter()
+                .map(|data| PivotRow::from_time(data.title.name(), &data.totals))
+                .collect(),
+            WeeklyReport::UserTimeWeeklyReport(report) => report.data
+                .iter()
+                .map(|data| PivotRow::from_time(data.title.name(), &data.totals))
+                .collect(),
+            WeeklyReport::ProjectEarningWeeklyReport(report) => report.data
+                .iter()
+                .map(|data| PivotRow::from_earnings(data.title.name(), &data.totals))
+                .collect(),
+            WeeklyReport::UserEarningWeeklyReport(report) => report.data
+                .iter()
+                .map(|data| PivotRow::from_earnings(data.title.name(), &data.totals))
+                .collect(),
+        };
+
+        WeeklyPivot { dates, rows }
+    }
+}
+
+/// A weekly report whose totals are labelled with calendar dates.
+///
+/// See `export::csv::CsvExporter::write_weekly_pivot()` and
+/// `export::table::TableRenderer::render_weekly_pivot()` to export it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeeklyPivot {
+    /// The 7 dates of the week, from `since`
+    pub dates: Vec<NaiveDate>,
+    /// A row for each project or user
+    pub rows: Vec<PivotRow>,
+}
+
+/// Totals of a project or user in `WeeklyPivot`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PivotRow {
+    pub title: String,
+    /// Values for each date of `WeeklyPivot::dates`
+    pub days: Vec<PivotValue>,
+    /// The total of the week
+    pub total: PivotValue,
+}
+
+impl PivotRow {
+    fn from_time(title: String, totals: &TimeTotals) -> Self {
+        let days = totals[..7].iter().map(|time| PivotValue::Time(**time)).collect();
+        let total = PivotValue::Time(*totals[7]);
+
+        Self { title, days, total }
+    }
+
+    fn from_earnings(title: String, totals: &EarningTotals) -> Self {
+        let value = |amount: f64| PivotValue::Earning {
+            amount,
+            currency: totals.currency.clone(),
+        };
+        let days = totals.amount[..7].iter().map(|amount| value(**amount)).collect();
+        let total = value(*totals.amount[7]);
+
+        Self { title, days, total }
+    }
+}
+
+/// A value of `WeeklyPivot`, which depends on `calculate` of the query
+#[derive(Clone, Debug, PartialEq)]
+pub enum PivotValue {
+    Time(Duration),
+    Earning {
+        amount: f64,
+        currency: Option<String>,
+    },
+}
+
+/// A seed to deserialize `WeeklyReport` as the variant which a query requests.
+pub(crate) struct WeeklyReportSeed<'a>(pub(crate) &'a Query);
+
+impl<'de> DeserializeSeed<'de> for WeeklyReportSeed<'_> {
+    type Value = WeeklyReport;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where D: Deserializer<'de>
+    {
+        let grouping = self.0.grouping.as_ref().unwrap_or(&GroupingKind::Projects);
+        let calculate = self.0.calculate.as_ref().unwrap_or(&CalculateKind::Time);
+
+        match (grouping, calculate) {
+            (GroupingKind::Projects, CalculateKind::Time) => Deserialize::deserialize(deserializer)
+                .map(WeeklyReport::ProjectTimeWeeklyReport),
+            (GroupingKind::Users, CalculateKind::Time) => Deserialize::deserialize(deserializer)
+                .map(WeeklyReport::UserTimeWeeklyReport),
+            (GroupingKind::Projects, CalculateKind::Earnings) => Deserialize::deserialize(deserializer)
+                .map(WeeklyReport::ProjectEarningWeeklyReport),
+            (GroupingKind::Users, CalculateKind::Earnings) => Deserialize::deserialize(deserializer)
+                .map(WeeklyReport::UserEarningWeeklyReport),
+            _ => WeeklyReport::deserialize(deserializer),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectData<Totals> {
     pub title: ProjectTitle,
     pub pid: Option<u64>,
@@ -34,14 +172,14 @@ pub struct ProjectData<Totals> {
     pub details: Vec<ProjectDetail<Totals>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectDetail<Totals> {
     pub uid: Option<u64>,
     pub title: UserTitle,
     pub totals: Totals,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct UserData<Totals> {
     pub title: UserTitle,
     pub uid: Option<u64>,
@@ -49,10 +187,214 @@ pub struct UserData<Totals> {
     pub details: Vec<UserDetail<Totals>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct UserDetail<Totals> {
     pub pid: Option<u64>,
     pub title: ProjectTitle,
     pub totals: Totals,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that a fixture decodes as the expected variant
+    macro_rules! fixture_test {
+        ($name:ident, $file:literal, $variant:ident) => {
+            #[test]
+            fn $name() {
+                let json = include_str!(concat!("../tests/fixtures/weekly/", $file, ".json"));
+                let report: WeeklyReport = serde_json::from_str(json).unwrap();
+
+                match report {
+                    WeeklyReport::$variant(report) => {
+                        assert_eq!(report.data.len(), 2);
+                        assert!(report.data.iter().all(|data| data.details.len() == 2));
+                    }
+                    other => panic!("unexpected variant: {:?}", other),
+                }
+            }
+        };
+    }
+
+    fixture_test!(projects_time, "projects_time", ProjectTimeWeeklyReport);
+    fixture_test!(users_time, "users_time", UserTimeWeeklyReport);
+    fixture_test!(projects_earnings, "projects_earnings", ProjectEarningWeeklyReport);
+    fixture_test!(users_earnings, "users_earnings", UserEarningWeeklyReport);
+
+    /// Asserts that a fixture decodes with a query as the expected variant
+    macro_rules! query_fixture_test {
+        ($name:ident, $file:literal, $grouping:ident, $calculate:ident, $variant:ident) => {
+            #[test]
+            fn $name() {
+                let json = include_str!(concat!("../tests/fixtures/weekly/", $file, ".json"));
+                let query = Query::new(334)
+                    .grouping(GroupingKind::$grouping)
+                    .calculate(CalculateKind::$calculate);
+                let report = WeeklyReport::from_json(json, &query).unwrap();
+
+                assert!(matches!(report, WeeklyReport::$variant(_)), "{:?}", report);
+            }
+        };
+    }
+
+    query_fixture_test!(query_projects_time, "projects_time", Projects, Time, ProjectTimeWeeklyReport);
+    query_fixture_test!(query_users_time, "users_time", Users, Time, UserTimeWeeklyReport);
+    query_fixture_test!(query_projects_earnings, "projects_earnings", Projects, Earnings, ProjectEarningWeeklyReport);
+    query_fixture_test!(query_users_earnings, "users_earnings", Users, Earnings, UserEarningWeeklyReport);
+    query_fixture_test!(query_empty_users_earnings, "empty", Users, Earnings, UserEarningWeeklyReport);
+    query_fixture_test!(query_empty_users_time, "empty", Users, Time, UserTimeWeeklyReport);
+
+    #[test]
+    fn from_json_default() {
+        let json = include_str!("../tests/fixtures/weekly/empty.json");
+        let report = WeeklyReport::from_json(json, &Query::new(334)).unwrap();
+
+        assert!(matches!(report, WeeklyReport::ProjectTimeWeeklyReport(_)));
+    }
+
+    #[test]
+    fn from_json_mismatch() {
+        let json = include_str!("../tests/fixtures/weekly/users_time.json");
+        let query = Query::new(334).grouping(GroupingKind::Projects);
+
+        assert!(WeeklyReport::from_json(json, &query).is_err());
+    }
+
+    /// Asserts that totals of a fixture add up from its groups and details
+    macro_rules! fixture_totals_test {
+        ($name:ident, $file:literal, $variant:ident, $sum:expr, $total:literal) => {
+            #[test]
+            fn $name() {
+                let json = include_str!(concat!("../tests/fixtures/weekly/", $file, ".json"));
+                let report = match serde_json::from_str(json).unwrap() {
+                    WeeklyReport::$variant(report) => report,
+                    other => panic!("unexpected variant: {:?}", other),
+                };
+
+                for data in &report.data {
+                    assert_eq!($sum(data.details.iter().map(|detail| &detail.totals).collect()), $sum(vec![&data.totals]));
+                }
+                let sums = $sum(report.data.iter().map(|data| &data.totals).collect());
+                assert_eq!(sums[..7].iter().sum::<f64>(), sums[7]);
+                assert_eq!(sums[7], $total);
+                assert_eq!(report.total_grand.num_hours(), 4);
+                assert_eq!(*report.total_currencies[0].amount, 40.0);
+            }
+        };
+    }
+
+    /// Adds up hours of each column
+    fn time_sums(totals: Vec<&TimeTotals>) -> Vec<f64> {
+        (0..8).map(|i| totals.iter().map(|totals| totals[i].num_minutes() as f64 / 60.0).sum()).collect()
+    }
+
+    /// Adds up amounts of each column
+    fn earning_sums(totals: Vec<&EarningTotals>) -> Vec<f64> {
+        (0..8).map(|i| totals.iter().map(|totals| *totals.amount[i]).sum()).collect()
+    }
+
+    fixture_totals_test!(projects_time_totals, "projects_time", ProjectTimeWeeklyReport, time_sums, 4.0);
+    fixture_totals_test!(users_time_totals, "users_time", UserTimeWeeklyReport, time_sums, 4.0);
+    fixture_totals_test!(projects_earnings_totals, "projects_earnings", ProjectEarningWeeklyReport, earning_sums, 40.0);
+    fixture_totals_test!(users_earnings_totals, "users_earnings", UserEarningWeeklyReport, earning_sums, 40.0);
+
+    #[test]
+    fn pivot_time() {
+        let json = include_str!("../tests/fixtures/weekly/projects_time.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let since = NaiveDate::from_ymd_opt(2021, 2, 26).unwrap();
+        let pivot = report.pivot(&WeeklyQuery::new(334).since_date(since));
+
+        let dates: Vec<String> = pivot.dates.iter().map(|date| date.to_string()).collect();
+        assert_eq!(dates, ["2021-02-26", "2021-02-27", "2021-02-28", "2021-03-01", "2021-03-02", "2021-03-03", "2021-03-04"]);
+        assert_eq!(pivot.rows.len(), 2);
+        assert_eq!(pivot.rows[0].title, "Toggl Development");
+        assert_eq!(pivot.rows[0].days[1], PivotValue::Time(Duration::hours(1)));
+        assert_eq!(pivot.rows[0].days[3], PivotValue::Time(Duration::minutes(30)));
+        assert_eq!(pivot.rows[0].total, PivotValue::Time(Duration::minutes(90)));
+    }
+
+    #[test]
+    fn pivot_earnings() {
+        let json = include_str!("../tests/fixtures/weekly/users_earnings.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let since = NaiveDate::from_ymd_opt(2021, 3, 1).unwrap();
+        let pivot = report.pivot(&WeeklyQuery::new(334).since_date(since));
+
+        assert_eq!(pivot.dates.len(), 7);
+        assert_eq!(pivot.dates[6], NaiveDate::from_ymd_opt(2021, 3, 7).unwrap());
+        assert_eq!(pivot.rows[0].title, "John Swift");
+        assert_eq!(pivot.rows[0].total, PivotValue::Earning {
+            amount: 15.0,
+            currency: Some(String::from("EUR")),
+        });
+    }
+
+    #[test]
+    fn pivot_default_since() {
+        let json = include_str!("../tests/fixtures/weekly/users_earnings.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+        let today = NaiveDate::from_ymd_opt(2021, 3, 7).unwrap();
+        let pivot = report.pivot_at(&WeeklyQuery::new(334), today);
+
+        assert_eq!(pivot.dates[0], NaiveDate::from_ymd_opt(2021, 3, 1).unwrap());
+        assert_eq!(pivot.dates[6], today);
+    }
+
+    #[test]
+    fn fixture_values() {
+        let json = include_str!("../tests/fixtures/weekly/users_earnings.json");
+        let report: WeeklyReport = serde_json::from_str(json).unwrap();
+
+        let report = match report {
+            WeeklyReport::UserEarningWeeklyReport(report) => report,
+            other => panic!("unexpected variant: {:?}", other),
+        };
+        let data = &report.data[0];
+        assert_eq!(data.title.name(), "John Swift");
+        assert_eq!(data.uid, Some(3000));
+        assert_eq!(data.totals.currency.as_deref(), Some("EUR"));
+        assert_eq!(*data.totals.amount[0], 0.0);
+        assert_eq!(*data.totals.amount[7], 15.0);
+        assert_eq!(data.details[1].title.project, None);
+        assert_eq!(data.details[1].pid, None);
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let json = serde_json::json!({
+            "total_grand": 36004000,
+            "total_billable": 14400000.0,
+            "total_currencies": [{"currency": "EUR", "amount": 40.0}],
+            "data": [{
+                "title": {"user": "John Swift"},
+                "uid": 1,
+                "totals": [null, 3600000, null, null, null, null, null, 3600000],
+                "details": [{
+                    "pid": 2,
+                    "title": {"project": "Toggl Development", "client": "Toggl"},
+                    "totals": [null, 3600000, null, null, null, null, null, 3600000]
+                }]
+            }]
+        });
+        let report: WeeklyReport = serde_json::from_value(json.clone()).unwrap();
+
+        let expected = serde_json::json!({
+            "total_grand": 36004000,
+            "total_billable": 14400000.0,
+            "total_currencies": [{"currency": "EUR", "amount": 40.0}],
+            "data": [{
+                "title": {"user": "John Swift"},
+                "uid": 1,
+                "totals": [0, 3600000, 0, 0, 0, 0, 0, 3600000],
+                "details": [{
+                    "pid": 2,
+                    "title": {"project": "Toggl Development", "client": "Toggl"},
+                    "totals": [0, 3600000, 0, 0, 0, 0, 0, 3600000]
+                }]
+            }]
+        });
+        assert_eq!(serde_json::to_value(&report).unwrap(), expected);
+    }
+}